@@ -0,0 +1,36 @@
+//generation_speed.rs
+//
+// Times each carving algorithm on a large square maze, best of three runs:
+//
+//     cargo run --release --example generation_speed [size]
+
+use genetic_algorithm_maze::maze::{Kruskals, Maze, MazeGenerator, Prims, RecursiveBacktracker, Wilsons};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 3;
+const SEED: u64 = 7;
+
+fn main() {
+    let size: usize = std::env::args().nth(1).and_then(|arg| arg.parse().ok()).unwrap_or(2000);
+
+    let generators: [(&str, &dyn MazeGenerator); 4] = [
+        ("recursive backtracker", &RecursiveBacktracker),
+        ("prim's", &Prims),
+        ("kruskal's", &Kruskals),
+        ("wilson's", &Wilsons),
+    ];
+
+    println!("{size}x{size} cells, best of {RUNS} runs");
+    for (name, generator) in generators {
+        let best = (0..RUNS)
+            .map(|_| {
+                let start = Instant::now();
+                black_box(Maze::with_generator(size, size, generator, SEED));
+                start.elapsed()
+            })
+            .min()
+            .unwrap_or(Duration::ZERO);
+        println!("{name:>22}: {:.3}s", best.as_secs_f64());
+    }
+}
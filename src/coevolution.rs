@@ -0,0 +1,107 @@
+//coevolution.rs
+
+use crate::genetics::{BitLayout, BitString, Decode, GeneticAlgorithm};
+use crate::maze::{Maze, Topology, WeightedKruskals};
+use ::rand::SeedableRng;
+use ::rand::rngs::StdRng;
+
+/// Bits per passage weight in a maze genome.
+pub const WEIGHT_BITS: usize = 4;
+
+/// A population of solvers and a population of mazes evolved against each other.
+///
+/// Solver genomes are routes, as in a single-maze run. Maze genomes are passage weights for
+/// [`WeightedKruskals`], so every one of them decodes to a perfect maze whose goal can be
+/// reached. Solvers are scored by their average fitness on the hardest mazes, and mazes by
+/// how poorly the best solvers do on them.
+pub struct Coevolution {
+    pub solvers: GeneticAlgorithm,
+    pub mazes: GeneticAlgorithm,
+    /// How many of the best of each population the other one is scored against.
+    pub opponents: usize,
+    dimension_x: usize,
+    dimension_y: usize,
+    topology: Topology,
+}
+
+impl Coevolution {
+    /// Random populations of `solver_count` routes of `route_length` moves and `maze_count`
+    /// mazes of the given size and cell shape, scored against each other once.
+    pub fn new(
+        dimension_x: usize,
+        dimension_y: usize,
+        topology: Topology,
+        route_length: usize,
+        solver_count: usize,
+        maze_count: usize,
+    ) -> Self {
+        let gene_length = build_maze((dimension_x, dimension_y, topology), Vec::new()).gene_length();
+        let weights = WeightedKruskals::weights_needed(dimension_x, dimension_y, topology);
+
+        let mut coevolution = Coevolution {
+            solvers: GeneticAlgorithm::new(0.7, 0.15, solver_count, 0.03, 500, BitLayout { length: route_length * gene_length, gene_length }),
+            mazes: GeneticAlgorithm::new(0.7, 0.01, maze_count, 0.1, 500, BitLayout { length: weights * WEIGHT_BITS, gene_length: WEIGHT_BITS }),
+            opponents: 5,
+            dimension_x,
+            dimension_y,
+            topology,
+        };
+
+        let mazes = coevolution.hardest_mazes(coevolution.opponents);
+        let layout = coevolution.solvers.shape;
+        coevolution.solvers.update_fitness(|bits| average_fitness(&mazes, &bits.decode(&layout)));
+        let routes = coevolution.champion_routes();
+        let (shape, layout) = (coevolution.shape(), coevolution.mazes.shape);
+        coevolution.mazes.update_fitness(|bits| hardness(shape, &routes, bits.decode(&layout)));
+        coevolution
+    }
+
+    /// Evolves the solvers one generation against the current hardest mazes, then the mazes
+    /// one generation against the new best solvers.
+    pub fn step(&mut self) {
+        let mazes = self.hardest_mazes(self.opponents);
+        let layout = self.solvers.shape;
+        self.solvers.epoch(|bits| average_fitness(&mazes, &bits.decode(&layout)));
+
+        let routes = self.champion_routes();
+        let (shape, layout) = (self.shape(), self.mazes.shape);
+        self.mazes.epoch(|bits| hardness(shape, &routes, bits.decode(&layout)));
+    }
+
+    /// The maze a maze genome describes.
+    pub fn decode_maze(&self, bits: &BitString) -> Maze {
+        build_maze(self.shape(), self.mazes.decode(bits))
+    }
+
+    /// Mazes of the `count` fittest maze genomes, hardest first.
+    pub fn hardest_mazes(&self, count: usize) -> Vec<Maze> {
+        self.mazes.fittest(count).into_iter().map(|genome| self.decode_maze(&genome.genes)).collect()
+    }
+
+    fn champion_routes(&self) -> Vec<Vec<u8>> {
+        self.solvers
+            .fittest(self.opponents)
+            .into_iter()
+            .map(|genome| self.solvers.decode(&genome.genes))
+            .collect()
+    }
+
+    fn shape(&self) -> (usize, usize, Topology) {
+        (self.dimension_x, self.dimension_y, self.topology)
+    }
+}
+
+fn build_maze((dimension_x, dimension_y, topology): (usize, usize, Topology), weights: Vec<u8>) -> Maze {
+    // The generator ignores the RNG, so any seed gives the same maze.
+    Maze::from_topology(dimension_x, dimension_y, topology, &WeightedKruskals { weights }, &mut StdRng::seed_from_u64(0))
+}
+
+fn average_fitness(mazes: &[Maze], route: &[u8]) -> f64 {
+    mazes.iter().map(|maze| maze.test_route(route.to_vec())).sum::<f64>() / mazes.len().max(1) as f64
+}
+
+/// How poorly `routes` do on the maze `weights` describe, as their negated average fitness.
+fn hardness(shape: (usize, usize, Topology), routes: &[Vec<u8>], weights: Vec<u8>) -> f64 {
+    let maze = build_maze(shape, weights);
+    -routes.iter().map(|route| maze.test_route(route.clone())).sum::<f64>() / routes.len().max(1) as f64
+}
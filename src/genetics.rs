@@ -1,40 +1,40 @@
 //genetics.rs
 
+mod chromosome;
+
+pub use chromosome::{BitLayout, BitString, Chromosome, Decode, DirectionLayout, Directions, Permutation, RealLayout, RealVector};
+
 use rand::prelude::*;
 use rayon::prelude::*;
 
-fn hamming_distance(a: &[u8], b: &[u8]) -> usize {
-    a.iter().zip(b.iter()).filter(|(x, y)| x != y).count()
-}
-
 #[derive(Clone, Debug)]
-pub struct Genome {
-    pub bits: Vec<u8>,
+pub struct Genome<C: Chromosome = BitString> {
+    pub genes: C,
     pub fitness: f64,
     pub stagnation: usize,
 }
 
-impl Genome {
-    pub fn new_random(num_bits: usize) -> Self {
-        let mut rng = thread_rng();
-        let bits: Vec<u8> = (0..num_bits).map(|_| rng.gen_range(0..=1)).collect();
+impl<C: Chromosome> Genome<C> {
+    pub fn new_random(shape: &C::Shape) -> Self {
         Self {
-            bits,
+            genes: C::random(shape, &mut thread_rng()),
             fitness: 0.0,
             stagnation: 0,
         }
     }
 }
 
-pub struct GeneticAlgorithm {
-    pub population: Vec<Genome>,
+/// A generational GA over any [`Chromosome`], with tournament selection, elitism and culling
+/// of genomes that stop improving. Fitness functions score a chromosome directly; the maze
+/// solver decodes its routes with [`GeneticAlgorithm::decode`].
+pub struct GeneticAlgorithm<C: Chromosome = BitString> {
+    pub population: Vec<Genome<C>>,
     pub pop_size: usize,
     pub elitism: f64,
     pub stagnation_limit: usize,
     pub crossover_rate: f64,
     pub mutation_rate: f64,
-    pub chromo_length: usize,
-    pub gene_length: usize,
+    pub shape: C::Shape,
 
     pub fittest_index: usize,
     pub best_fitness: f64,
@@ -42,8 +42,8 @@ pub struct GeneticAlgorithm {
     pub generation: usize,
 }
 
-impl GeneticAlgorithm {
-    pub fn new(crossover_rate: f64, mutation_rate: f64, pop_size: usize, elitism: f64, stagnation_limit: usize, chromo_length: usize, gene_length: usize) -> Self {
+impl<C: Chromosome> GeneticAlgorithm<C> {
+    pub fn new(crossover_rate: f64, mutation_rate: f64, pop_size: usize, elitism: f64, stagnation_limit: usize, shape: C::Shape) -> Self {
         let mut algo = Self {
             population: Vec::with_capacity(pop_size),
             pop_size,
@@ -51,8 +51,7 @@ impl GeneticAlgorithm {
             stagnation_limit,
             crossover_rate,
             mutation_rate,
-            chromo_length,
-            gene_length,
+            shape,
             fittest_index: 0,
             best_fitness: 0.0,
             total_fitness: 0.0,
@@ -64,34 +63,24 @@ impl GeneticAlgorithm {
 
     fn create_start_population(&mut self) {
         self.population = (0..self.pop_size)
-            .map(|_| Genome::new_random(self.chromo_length))
+            .map(|_| Genome::new_random(&self.shape))
             .collect();
     }
 
-    fn mutate(&self, bits: &mut [u8]) {
-        let mut rng = thread_rng();
-        for bit in bits.iter_mut() {
-            if rng.r#gen::<f64>() < self.mutation_rate {
-                *bit ^= 1;
-            }
-        }
+    fn mutate(&self, genes: &mut C) {
+        genes.mutate(&self.shape, self.mutation_rate, &mut thread_rng());
     }
 
-    fn crossover(&self, mom: &[u8], dad: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    fn crossover(&self, mom: &C, dad: &C) -> (C, C) {
         let mut rng = thread_rng();
         if rng.r#gen::<f64>() > self.crossover_rate || mom == dad {
-            return (mom.to_vec(), dad.to_vec());
+            return (mom.clone(), dad.clone());
         }
 
-        let cp = rng.gen_range(0..self.chromo_length);
-        let mut baby1 = mom[..cp].to_vec();
-        baby1.extend_from_slice(&dad[cp..]);
-        let mut baby2 = dad[..cp].to_vec();
-        baby2.extend_from_slice(&mom[cp..]);
-        (baby1, baby2)
+        mom.crossover(dad, &mut rng)
     }
 
-    fn roulette_selection(&self) -> &Genome {
+    fn _roulette_selection(&self) -> &Genome<C> {
         let mut rng = thread_rng();
         let slice = rng.r#gen::<f64>() * self.total_fitness;
         let mut total = 0.0;
@@ -104,7 +93,7 @@ impl GeneticAlgorithm {
         &self.population[0]
     }
 
-    fn tournament_selection(&self, k: usize) -> &Genome {
+    fn tournament_selection(&self, k: usize) -> &Genome<C> {
         let mut rng = thread_rng();
         let mut best = &self.population[rng.gen_range(0..self.pop_size)];
 
@@ -117,49 +106,45 @@ impl GeneticAlgorithm {
         best
     }
 
-    pub fn decode(&self, bits: &[u8]) -> Vec<u8> {
-        let mut decoded = Vec::with_capacity(bits.len() / 2);
-        for chunk in bits.chunks(2) {
-            if chunk.len() != 2 {
-                // Log warning or skip last bit if chunk is incomplete
-                continue;
-            }
-            let dir = match chunk {
-                [0, 0] => 0,
-                [0, 1] => 1,
-                [1, 0] => 2,
-                [1, 1] => 3,
-                _ => 0, // default fallback
-            };
-            decoded.push(dir);
-        }
-        decoded
+    pub fn update_fitness<F>(&mut self, test_route: F)
+    where
+        F: Fn(&C) -> f64 + Send + Sync,
+    {
+        let fitness_scores = self.score_population(test_route);
+        self.record_fitness(fitness_scores, true);
     }
 
-    pub fn update_fitness<F>(&mut self, test_route: F)
+    /// Rescores the current population after the environment changed, without counting
+    /// the change towards any genome's stagnation.
+    pub fn reevaluate<F>(&mut self, test_route: F)
     where
-        F: Fn(Vec<u8>) -> f64 + Send + Sync + Copy,
+        F: Fn(&C) -> f64 + Send + Sync,
     {
-        let decoded_paths: Vec<Vec<u8>> = self
-            .population
-            .par_iter()
-            .map(|genome| self.decode(&genome.bits))
-            .collect();
+        let fitness_scores = self.score_population(test_route);
+        self.record_fitness(fitness_scores, false);
+    }
 
-        let fitness_scores: Vec<f64> = decoded_paths
-            .par_iter()
-            .map(|decoded| test_route(decoded.clone()))
-            .collect();
+    fn score_population<F>(&self, test_route: F) -> Vec<f64>
+    where
+        F: Fn(&C) -> f64 + Send + Sync,
+    {
+        self.population.par_iter().map(|genome| test_route(&genome.genes)).collect()
+    }
 
+    /// Stores new fitness scores and recomputes the total and the fittest genome. With
+    /// `count_stagnation`, a genome that did not improve is one generation more stagnant.
+    fn record_fitness(&mut self, fitness_scores: Vec<f64>, count_stagnation: bool) {
         self.total_fitness = 0.0;
         self.best_fitness = f64::NEG_INFINITY;
         self.fittest_index = 0;
 
         for (i, (genome, fitness)) in self.population.iter_mut().zip(fitness_scores).enumerate() {
-            if fitness > genome.fitness {
-                genome.stagnation = 0;
-            } else {
-                genome.stagnation += 1;
+            if count_stagnation {
+                if fitness > genome.fitness {
+                    genome.stagnation = 0;
+                } else {
+                    genome.stagnation += 1;
+                }
             }
 
             genome.fitness = fitness;
@@ -175,7 +160,7 @@ impl GeneticAlgorithm {
 
     pub fn epoch<F>(&mut self, test_route: F)
     where
-        F: Fn(Vec<u8>) -> f64 + Send + Sync + Copy,
+        F: Fn(&C) -> f64 + Send + Sync,
     {
         self.population.retain(|g| g.stagnation < self.stagnation_limit);
         let culled_count = self.pop_size - self.population.len();
@@ -192,21 +177,21 @@ impl GeneticAlgorithm {
         while new_population.len() + 1 < self.pop_size {
             let mom = self.tournament_selection(3);
             let dad = self.tournament_selection(3);
-            let (mut baby1_bits, mut baby2_bits) = self.crossover(&mom.bits, &dad.bits);
-            self.mutate(&mut baby1_bits);
-            self.mutate(&mut baby2_bits);
+            let (mut baby1_genes, mut baby2_genes) = self.crossover(&mom.genes, &dad.genes);
+            self.mutate(&mut baby1_genes);
+            self.mutate(&mut baby2_genes);
 
-             let avg_stagnation = ((mom.stagnation + dad.stagnation) / 2).max(0);
+            let avg_stagnation = (mom.stagnation + dad.stagnation) / 2;
 
             new_population.push(Genome {
-                bits: baby1_bits,
+                genes: baby1_genes,
                 fitness: 0.0,
                 stagnation: avg_stagnation,
             });
 
             if new_population.len() < self.pop_size {
                 new_population.push(Genome {
-                    bits: baby2_bits,
+                    genes: baby2_genes,
                     fitness: 0.0,
                     stagnation: avg_stagnation,
                 });
@@ -220,20 +205,8 @@ impl GeneticAlgorithm {
 
 
     pub fn inject_random_individuals(&mut self, count: usize) {
-        use rand::Rng;
         for _ in 0..count {
-            let random_bits: Vec<u8> = (0..self.chromo_length)
-                .map(|_| rand::thread_rng().gen_range(0..=1))
-                .collect();
-
-            let fitness = 0.0;
-            let stagnation = 0;
-
-            self.population.push(Genome {
-                bits: random_bits,
-                fitness,
-                stagnation,
-            });
+            self.population.push(Genome::new_random(&self.shape));
         }
 
         if self.population.len() > self.pop_size {
@@ -253,33 +226,37 @@ impl GeneticAlgorithm {
         self.mutation_rate = rate;
     }
 
-    pub fn average_hamming_distance(&self, top_n: usize) -> f64 {
-        let sorted = {
-            let mut pop = self.population.clone();
-            pop.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
-            pop.into_iter().take(top_n).collect::<Vec<_>>()
-        };
+    /// Up to `count` genomes, fittest first.
+    pub fn fittest(&self, count: usize) -> Vec<&Genome<C>> {
+        let mut sorted: Vec<&Genome<C>> = self.population.iter().collect();
+        sorted.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap_or(std::cmp::Ordering::Equal));
+        sorted.truncate(count);
+        sorted
+    }
+
+    /// Average [`Chromosome::distance`] between each pair of the `top_n` fittest genomes.
+    pub fn average_distance(&self, top_n: usize) -> f64 {
+        let sorted = self.fittest(top_n);
 
         if sorted.len() < 2 {
             return 0.0;
         }
 
-        let mut total_distance = 0usize;
+        let mut total_distance = 0.0;
         let mut count = 0usize;
 
         for i in 0..sorted.len() {
             for j in (i + 1)..sorted.len() {
-                let dist = hamming_distance(&sorted[i].bits, &sorted[j].bits);
-                total_distance += dist;
+                total_distance += sorted[i].genes.distance(&sorted[j].genes);
                 count += 1;
             }
         }
 
-        total_distance as f64 / count as f64
+        total_distance / count as f64
     }
 
     pub fn adapt_mutation_rate(&mut self, min_rate: f64, max_rate: f64, target_diversity: f64) {
-        let diversity = self.average_hamming_distance((self.elitism * self.pop_size as f64).ceil() as usize);
+        let diversity = self.average_distance((self.elitism * self.pop_size as f64).ceil() as usize);
 
         if diversity < target_diversity {
             self.mutation_rate = (self.mutation_rate * 1.1).min(max_rate);
@@ -289,3 +266,10 @@ impl GeneticAlgorithm {
     }
 
 }
+
+impl<C: Decode> GeneticAlgorithm<C> {
+    /// The moves a chromosome of this population encodes.
+    pub fn decode(&self, genes: &C) -> Vec<u8> {
+        genes.decode(&self.shape)
+    }
+}
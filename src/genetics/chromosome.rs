@@ -0,0 +1,261 @@
+//chromosome.rs
+
+use rand::prelude::*;
+
+/// The genetic material of a [`Genome`](super::Genome): how it is created, varied and compared.
+///
+/// Everything a population has in common, such as its chromosome length, lives in `Shape` and
+/// is passed in, so chromosomes themselves are plain values.
+pub trait Chromosome: Clone + PartialEq + Send + Sync {
+    type Shape: Clone + Send + Sync;
+
+    fn random(shape: &Self::Shape, rng: &mut dyn RngCore) -> Self;
+
+    /// Changes each gene with probability `rate`.
+    fn mutate(&mut self, shape: &Self::Shape, rate: f64, rng: &mut dyn RngCore);
+
+    /// Two children that share the parents' genes between them.
+    fn crossover(&self, other: &Self, rng: &mut dyn RngCore) -> (Self, Self);
+
+    /// How different two chromosomes are, zero for identical ones.
+    fn distance(&self, other: &Self) -> f64;
+
+    /// Number of genes, each of which mutates on its own.
+    fn gene_count(&self) -> usize;
+}
+
+/// A chromosome that reads as a list of small symbols, such as the moves of a route.
+pub trait Decode: Chromosome {
+    fn decode(&self, shape: &Self::Shape) -> Vec<u8>;
+}
+
+/// A string of 0 and 1 bits, read in `gene_length`-bit groups when decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitString(pub Vec<u8>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitLayout {
+    /// Bits per chromosome.
+    pub length: usize,
+    /// Bits per gene when decoded.
+    pub gene_length: usize,
+}
+
+impl Decode for BitString {
+    /// Splits the bits into `gene_length`-bit genes and reads each one as a big-endian
+    /// number, so 2-bit genes give 0 to 3 and 3-bit genes give 0 to 7. A short last gene
+    /// is dropped. Codes are not folded into a smaller range, so when fewer symbols are
+    /// needed than a gene can spell, the caller decides what the spare codes mean.
+    fn decode(&self, shape: &BitLayout) -> Vec<u8> {
+        self.0
+            .chunks_exact(shape.gene_length)
+            .map(|chunk| chunk.iter().fold(0, |acc, &bit| (acc << 1) | bit))
+            .collect()
+    }
+}
+
+impl Chromosome for BitString {
+    type Shape = BitLayout;
+
+    fn random(shape: &BitLayout, rng: &mut dyn RngCore) -> Self {
+        BitString((0..shape.length).map(|_| rng.gen_range(0..=1)).collect())
+    }
+
+    fn mutate(&mut self, _shape: &BitLayout, rate: f64, rng: &mut dyn RngCore) {
+        for bit in self.0.iter_mut() {
+            if rng.r#gen::<f64>() < rate {
+                *bit ^= 1;
+            }
+        }
+    }
+
+    fn crossover(&self, other: &Self, rng: &mut dyn RngCore) -> (Self, Self) {
+        let (a, b) = single_point(&self.0, &other.0, rng);
+        (BitString(a), BitString(b))
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        hamming_distance(&self.0, &other.0)
+    }
+
+    /// Bits, since each one mutates on its own.
+    fn gene_count(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// One symbol per gene, each below the layout's number of directions.
+///
+/// Unlike a [`BitString`], mutation swaps a whole direction for another and crossover can
+/// only cut between genes, never through one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Directions(pub Vec<u8>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DirectionLayout {
+    pub length: usize,
+    pub directions: u8,
+}
+
+impl DirectionLayout {
+    /// `length` genes over `directions` symbols, one for each move a route can make. A
+    /// [`BitLayout`] rounds its alphabet up to a power of two instead, so its genes can also
+    /// spell codes that are not moves.
+    pub fn with_directions(length: usize, directions: usize) -> Self {
+        DirectionLayout { length, directions: directions as u8 }
+    }
+}
+
+impl Decode for Directions {
+    fn decode(&self, _shape: &DirectionLayout) -> Vec<u8> {
+        self.0.clone()
+    }
+}
+
+impl Chromosome for Directions {
+    type Shape = DirectionLayout;
+
+    fn random(shape: &DirectionLayout, rng: &mut dyn RngCore) -> Self {
+        Directions((0..shape.length).map(|_| rng.gen_range(0..shape.directions.max(1))).collect())
+    }
+
+    /// Replaces a mutated gene with a different direction.
+    fn mutate(&mut self, shape: &DirectionLayout, rate: f64, rng: &mut dyn RngCore) {
+        if shape.directions < 2 {
+            return;
+        }
+        for gene in self.0.iter_mut() {
+            if rng.r#gen::<f64>() < rate {
+                *gene = (*gene + rng.gen_range(1..shape.directions)) % shape.directions;
+            }
+        }
+    }
+
+    fn crossover(&self, other: &Self, rng: &mut dyn RngCore) -> (Self, Self) {
+        let (a, b) = single_point(&self.0, &other.0, rng);
+        (Directions(a), Directions(b))
+    }
+
+    fn distance(&self, other: &Self) -> f64 {
+        hamming_distance(&self.0, &other.0)
+    }
+
+    fn gene_count(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Real numbers kept within the layout's bounds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RealVector(pub Vec<f64>);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RealLayout {
+    pub length: usize,
+    pub min: f64,
+    pub max: f64,
+    /// Largest change a mutation makes, as a fraction of `max - min`.
+    pub step: f64,
+}
+
+impl Chromosome for RealVector {
+    type Shape = RealLayout;
+
+    fn random(shape: &RealLayout, rng: &mut dyn RngCore) -> Self {
+        RealVector((0..shape.length).map(|_| shape.min + rng.r#gen::<f64>() * (shape.max - shape.min)).collect())
+    }
+
+    /// Nudges a mutated value up or down by up to `step` of the range.
+    fn mutate(&mut self, shape: &RealLayout, rate: f64, rng: &mut dyn RngCore) {
+        let reach = (shape.max - shape.min) * shape.step;
+        for value in self.0.iter_mut() {
+            if rng.r#gen::<f64>() < rate {
+                *value = (*value + (rng.r#gen::<f64>() * 2.0 - 1.0) * reach).clamp(shape.min, shape.max);
+            }
+        }
+    }
+
+    fn crossover(&self, other: &Self, rng: &mut dyn RngCore) -> (Self, Self) {
+        let (a, b) = single_point(&self.0, &other.0, rng);
+        (RealVector(a), RealVector(b))
+    }
+
+    /// Euclidean distance.
+    fn distance(&self, other: &Self) -> f64 {
+        self.0.iter().zip(&other.0).map(|(a, b)| (a - b) * (a - b)).sum::<f64>().sqrt()
+    }
+
+    fn gene_count(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// An ordering of `0..length`, for problems such as visiting waypoints in the best order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Permutation(pub Vec<usize>);
+
+impl Chromosome for Permutation {
+    /// Number of items ordered.
+    type Shape = usize;
+
+    fn random(shape: &usize, rng: &mut dyn RngCore) -> Self {
+        let mut order: Vec<usize> = (0..*shape).collect();
+        order.shuffle(rng);
+        Permutation(order)
+    }
+
+    /// Swaps a mutated position with a random other one.
+    fn mutate(&mut self, _shape: &usize, rate: f64, rng: &mut dyn RngCore) {
+        let len = self.0.len();
+        for i in 0..len {
+            if rng.r#gen::<f64>() < rate {
+                self.0.swap(i, rng.gen_range(0..len));
+            }
+        }
+    }
+
+    /// Order crossover: each child keeps a slice of one parent in place and fills the rest
+    /// in the order the items appear in the other parent.
+    fn crossover(&self, other: &Self, rng: &mut dyn RngCore) -> (Self, Self) {
+        let len = self.0.len();
+        if len < 2 {
+            return (self.clone(), other.clone());
+        }
+        let (mut start, mut end) = (rng.gen_range(0..len), rng.gen_range(0..len));
+        if start > end {
+            std::mem::swap(&mut start, &mut end);
+        }
+
+        let child = |kept: &[usize], filler: &[usize]| {
+            let slice = &kept[start..=end];
+            let mut rest = filler.iter().copied().filter(|item| !slice.contains(item));
+            let order = (0..len)
+                .map(|i| if (start..=end).contains(&i) { kept[i] } else { rest.next().unwrap_or(kept[i]) })
+                .collect();
+            Permutation(order)
+        };
+        (child(&self.0, &other.0), child(&other.0, &self.0))
+    }
+
+    /// Number of positions holding different items.
+    fn distance(&self, other: &Self) -> f64 {
+        hamming_distance(&self.0, &other.0)
+    }
+
+    fn gene_count(&self) -> usize {
+        self.0.len()
+    }
+}
+
+fn single_point<T: Clone>(mom: &[T], dad: &[T], rng: &mut dyn RngCore) -> (Vec<T>, Vec<T>) {
+    let cp = rng.gen_range(0..mom.len().min(dad.len()).max(1));
+    let mut baby1 = mom[..cp].to_vec();
+    baby1.extend_from_slice(&dad[cp..]);
+    let mut baby2 = dad[..cp].to_vec();
+    baby2.extend_from_slice(&mom[cp..]);
+    (baby1, baby2)
+}
+
+fn hamming_distance<T: PartialEq>(a: &[T], b: &[T]) -> f64 {
+    a.iter().zip(b.iter()).filter(|(x, y)| x != y).count() as f64
+}
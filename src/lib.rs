@@ -0,0 +1,7 @@
+//lib.rs
+
+pub mod coevolution;
+pub mod genetics;
+pub mod maze;
+pub mod stats;
+pub mod visualizer;
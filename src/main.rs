@@ -1,80 +1,269 @@
 //main.rs
 
-mod maze;
-mod genetics;
-mod visualizer;
-
 use macroquad::prelude::*;
-use maze::Maze;
-use visualizer::Visualizer;
-use genetics::GeneticAlgorithm;
+use genetic_algorithm_maze::maze::{Collision, Maze, RecursiveBacktracker, Topology};
+use genetic_algorithm_maze::visualizer::Visualizer;
+use genetic_algorithm_maze::genetics::{BitLayout, BitString, Chromosome, Decode, DirectionLayout, Directions, GeneticAlgorithm};
+use genetic_algorithm_maze::coevolution::Coevolution;
+use genetic_algorithm_maze::stats::RunStatistics;
 use macroquad::window::Conf;
+use ::rand::SeedableRng;
+use ::rand::rngs::StdRng;
 
 const MAZE_WIDTH: usize = 15;
 const MAZE_HEIGHT: usize = 15;
 const CELL_SIZE: f32 = 20.0;
+const TOPOLOGY: Topology = Topology::Square;
+// Floors are drawn side by side; more than one level needs the square topology.
+const LEVELS: usize = 1;
+// Square mazes whose edges wrap around; ignores TOPOLOGY and LEVELS.
+const WRAP: bool = false;
+const ROUTE_LENGTH: usize = 1024;
+
+// Braids the maze and adds doors that open and close every DOOR_PERIOD generations.
+const DYNAMIC_DOORS: bool = false;
+const DOOR_COUNT: usize = 4;
+const DOOR_PERIOD: usize = 40;
+const DOOR_OPEN_FOR: usize = 20;
+
+// Places locked doors on the shortest route, each opened by a key elsewhere in the maze.
+const LOCKED_DOORS: bool = false;
+const LOCK_COUNT: usize = 3;
+
+// Scatters hazards that end any route stepping on them.
+const HAZARDS: bool = false;
+const HAZARD_COUNT: usize = 10;
+const HAZARD_PENALTY: f64 = 100.0;
+
+// Adds patrols pacing back and forth and chasers that move every CHASER_PERIOD steps.
+const ADVERSARIES: bool = false;
+const PATROL_COUNT: usize = 3;
+const PATROL_LENGTH: usize = 6;
+const CHASER_COUNT: usize = 1;
+const CHASER_PERIOD: usize = 2;
+const COLLISION: Collision = Collision::EndRoute(100.0);
+
+// Evolves mazes against a population of solvers first and solves the hardest one; ignores LEVELS and WRAP.
+const COEVOLUTION: bool = false;
+const COEVOLUTION_GENERATIONS: usize = 100;
+const COEVOLUTION_SOLVERS: usize = 200;
+const COEVOLUTION_MAZES: usize = 50;
+
+// Encodes each move as one direction symbol instead of `gene_length` bits.
+const DIRECTION_ENCODING: bool = false;
+// Evolves both encodings on the maze without drawing before the visual run, and prints their convergence.
+const COMPARE_ENCODINGS: bool = false;
+const COMPARE_GENERATIONS: usize = 300;
 
 fn window_conf() -> Conf {
+    let (width, height) = if WRAP {
+        ((MAZE_WIDTH * 2) as f32, (MAZE_HEIGHT * 2) as f32)
+    } else {
+        TOPOLOGY.canvas_size(MAZE_WIDTH * LEVELS, MAZE_HEIGHT)
+    };
+
     Conf {
         window_title: "Genetic Maze Solver".to_string(),
-        window_width: ((MAZE_WIDTH * 2 + 1) as f32 * CELL_SIZE) as i32,
-        window_height: ((MAZE_HEIGHT * 2 + 1) as f32 * CELL_SIZE) as i32,
+        window_width: (width * CELL_SIZE).ceil() as i32,
+        window_height: (height * CELL_SIZE).ceil() as i32,
         ..Default::default()
     }
 }
 
 #[macroquad::main(window_conf)]
 async fn main() {
-    let maze = Maze::new(MAZE_WIDTH, MAZE_HEIGHT);
-    let visualizer = Visualizer::new(maze.get_grid());
+    let seed = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse::<u64>().ok())
+        .unwrap_or_else(::rand::random);
+    let mut maze = if WRAP {
+        Maze::with_wraparound(MAZE_WIDTH, MAZE_HEIGHT, &RecursiveBacktracker, seed)
+    } else if LEVELS > 1 {
+        Maze::with_levels(MAZE_WIDTH, MAZE_HEIGHT, LEVELS, &RecursiveBacktracker, seed)
+    } else {
+        Maze::with_topology(MAZE_WIDTH, MAZE_HEIGHT, TOPOLOGY, &RecursiveBacktracker, seed)
+    };
+    println!("Maze seed: {}", maze.seed().unwrap_or_default());
+
+    if COEVOLUTION {
+        let mut coevolution = Coevolution::new(MAZE_WIDTH, MAZE_HEIGHT, TOPOLOGY, ROUTE_LENGTH, COEVOLUTION_SOLVERS, COEVOLUTION_MAZES);
+        for _ in 0..COEVOLUTION_GENERATIONS {
+            coevolution.step();
+        }
+        maze = coevolution.hardest_mazes(1).remove(0);
+        println!("Coevolved maze: {COEVOLUTION_GENERATIONS} generations, hardness {:.1}", coevolution.mazes.best_fitness);
+    }
+
+    if DYNAMIC_DOORS {
+        let mut rng = StdRng::seed_from_u64(maze.seed().unwrap_or_default());
+        maze.braid(0.5, &mut rng);
+        let added = maze.add_random_doors(DOOR_COUNT, DOOR_PERIOD, DOOR_OPEN_FOR, &mut rng);
+        maze.update_doors(0);
+        println!("Dynamic maze: {added} doors, period {DOOR_PERIOD}");
+    }
+
+    if LOCKED_DOORS {
+        let mut rng = StdRng::seed_from_u64(maze.seed().unwrap_or_default());
+        let added = maze.add_random_locks(LOCK_COUNT, &mut rng);
+        println!("Locked doors: {added}");
+    }
+
+    if HAZARDS {
+        let mut rng = StdRng::seed_from_u64(maze.seed().unwrap_or_default());
+        let added = maze.add_random_hazards(HAZARD_COUNT, &mut rng);
+        maze.set_hazard_penalty(HAZARD_PENALTY);
+        println!("Hazards: {added}, penalty {HAZARD_PENALTY}");
+    }
+
+    if ADVERSARIES {
+        let mut rng = StdRng::seed_from_u64(maze.seed().unwrap_or_default());
+        let patrols = maze.add_random_patrols(PATROL_COUNT, PATROL_LENGTH, &mut rng);
+        let chasers = maze.add_random_chasers(CHASER_COUNT, CHASER_PERIOD, &mut rng);
+        maze.set_collision(COLLISION);
+        println!("Adversaries: {patrols} patrols, {chasers} chasers, {COLLISION:?} on collision");
+    }
+
+    println!("{}", maze.metrics());
+    match maze.solve_dijkstra() {
+        Some(optimal) => println!("Optimal route: {} steps, cost {:.1}\n", optimal.steps, optimal.cost),
+        None => println!("Optimal route: none, the goal is unreachable\n"),
+    }
 
-    let mut ga = GeneticAlgorithm::new(
-        0.7,
-        0.15,
-        600,
-        0.03,
-        500,
-        2048,
-        2,
-    );
+    let visualizer = Visualizer::new(maze.get_grid())
+        .with_waypoints(maze.waypoints().to_vec())
+        .with_locks(maze.locks().to_vec())
+        .with_hazards(maze.hazards().to_vec())
+        .with_geometry(&maze);
 
-    ga.update_fitness(|route| maze.test_route(route));
+    let gene_length = maze.gene_length();
+    let bits = BitLayout { length: ROUTE_LENGTH * gene_length, gene_length };
+    let directions = DirectionLayout::with_directions(ROUTE_LENGTH, maze.directions());
+
+    if COMPARE_ENCODINGS {
+        let (bit_stats, bit_goal) = run_headless(new_population::<BitString>(bits), &mut maze);
+        let (direction_stats, direction_goal) = run_headless(new_population::<Directions>(directions), &mut maze);
+
+        println!(
+            "Bits: {} codes per move from {gene_length}-bit genes. Directions: {} symbols, one per move.",
+            1 << gene_length,
+            maze.directions()
+        );
+        println!("{:>4} | {:>12} | {:>12}", "Gen.", "Bits", "Directions");
+        println!("{:-<4}-+-{:-<12}-+-{:-<12}", "", "", "");
+        for (bit, direction) in bit_stats.generations.iter().zip(&direction_stats.generations).step_by(25) {
+            println!("{:>4} | {:>12.2} | {:>12.2}", bit.generation, bit.best_fitness, direction.best_fitness);
+        }
+        let first = |goal: Option<usize>| goal.map_or("never".to_string(), |generation| generation.to_string());
+        println!("Goal first reached: bits {}, directions {}\n", first(bit_goal), first(direction_goal));
+    }
+
+    if DIRECTION_ENCODING {
+        evolve(new_population::<Directions>(directions), maze, visualizer).await;
+    } else {
+        evolve(new_population::<BitString>(bits), maze, visualizer).await;
+    }
+}
+
+fn new_population<C: Chromosome>(shape: C::Shape) -> GeneticAlgorithm<C> {
+    GeneticAlgorithm::new(0.7, 0.15, 600, 0.03, 500, shape)
+}
+
+/// Runs one generation, opening and closing doors on schedule, and adapts the mutation rate.
+/// Returns a description of the doors that changed, if any did.
+fn advance<C: Decode>(ga: &mut GeneticAlgorithm<C>, maze: &mut Maze, stats: &mut RunStatistics) -> Option<String> {
+    let shape = ga.shape.clone();
+    ga.epoch(|genes| maze.test_route(genes.decode(&shape)));
+
+    let change = maze.update_doors(ga.generation);
+    let description = (!change.is_empty()).then(|| {
+        let best_before = ga.best_fitness;
+        ga.reevaluate(|genes| maze.test_route(genes.decode(&shape)));
+
+        let description = format!("{} doors opened, {} closed", change.opened.len(), change.closed.len());
+        stats.record_environment_change(ga.generation, description.clone(), best_before, ga.best_fitness);
+        description
+    });
+    stats.record_generation(ga);
+
+    //let adaptive_mutation = 0.15 * (1.0 - (generation as f32 / 100.0).clamp(0.05, 1.0)) as f64;
+    //ga.set_mutation_rate(adaptive_mutation);
+
+    let min_mutation = 0.05;
+    let max_mutation = 0.3;
+    let genes = ga.population.first().map_or(0, |genome| genome.genes.gene_count());
+    let target_diversity = genes as f64 * 0.1; // e.g., 10% of genes differ on average among elites
+
+    ga.adapt_mutation_rate(min_mutation, max_mutation, target_diversity);
+
+    if ga.generation.is_multiple_of(10) {
+        ga.inject_random_individuals(5);
+    }
+
+    description
+}
+
+/// Evolves `ga` for COMPARE_GENERATIONS generations without drawing. Returns its statistics
+/// and the first generation whose best route reached the goal, and leaves the doors as they
+/// were at the start.
+fn run_headless<C: Decode>(mut ga: GeneticAlgorithm<C>, maze: &mut Maze) -> (RunStatistics, Option<usize>) {
+    let shape = ga.shape.clone();
+    ga.update_fitness(|genes| maze.test_route(genes.decode(&shape)));
+
+    let mut stats = RunStatistics::new();
+    let mut goal = None;
+    for _ in 0..COMPARE_GENERATIONS {
+        advance(&mut ga, maze, &mut stats);
+
+        if goal.is_none() && !ga.population.is_empty() {
+            let route = ga.decode(&ga.population[ga.fittest_index].genes);
+            let path = maze.trace_route(&route);
+            if path.last().copied() == maze.end_pos() && maze.waypoint_pickups(&path).iter().all(Option::is_some) {
+                goal = Some(ga.generation);
+            }
+        }
+    }
+
+    maze.update_doors(0);
+    (stats, goal)
+}
+
+async fn evolve<C: Decode>(mut ga: GeneticAlgorithm<C>, mut maze: Maze, mut visualizer: Visualizer) {
+    let shape = ga.shape.clone();
+    let gene_length = maze.gene_length();
+    ga.update_fitness(|genes| maze.test_route(genes.decode(&shape)));
 
     let mut goal_reached = false;
     let mut final_path = Vec::new();
+    let mut final_pickups = Vec::new();
+    let mut final_key_pickups = Vec::new();
+    let mut final_adversaries = Vec::new();
     let mut generation = 0;
+    let mut stats = RunStatistics::new();
 
     println!("{:>4} | {:>12} | {:<36}", "Gen.", "Fitness", "Genome");
     println!("{:-<4}-+-{:-<12}-+-{:-<36}", "", "", "");
 
     loop {
         if !goal_reached {
-            ga.epoch(|genome| {
-                let decoded = genome.clone();
-                maze.test_route(decoded)
-            });
+            if let Some(description) = advance(&mut ga, &mut maze, &mut stats) {
+                visualizer.maze_grid = maze.get_grid();
+                println!("{:>4} | {:>12.2} | {description}", generation, ga.best_fitness);
+            }
 
-            let (best_route, best_bits, fitness) = {
+            let (best_route, fitness) = {
                 if ga.population.is_empty() {
                     continue;
                 }
 
                 let best = &ga.population[ga.fittest_index];
-                (ga.decode(&best.bits), best.bits.clone(), best.fitness)
+                (ga.decode(&best.genes), best.fitness)
             };
 
-            //let adaptive_mutation = 0.15 * (1.0 - (generation as f32 / 100.0).clamp(0.05, 1.0)) as f64;
-            //ga.set_mutation_rate(adaptive_mutation);
-
-            let min_mutation = 0.05;
-            let max_mutation = 0.3;
-            let target_diversity = (ga.chromo_length as f64) * 0.1; // e.g., 10% bits differ on average among elites
-
-            ga.adapt_mutation_rate(min_mutation, max_mutation, target_diversity);
-
-            if generation % 10 == 0 {
-                ga.inject_random_individuals(5);
-            }
+            // Both encodings are shown as the bits of their moves, `gene_length` bits each.
+            let best_bits: Vec<u8> = best_route
+                .iter()
+                .flat_map(|&direction| (0..gene_length).rev().map(move |i| (direction >> i) & 1))
+                .collect();
 
             let hex_str = |bits: &[u8]| -> String {
                 let byte_chunks = bits.chunks(8).collect::<Vec<_>>();
@@ -101,34 +290,25 @@ async fn main() {
 
             generation += 1;
 
-            let mut pos = maze.start_pos().unwrap();
-            let mut path = vec![pos];
-
-            for dir in best_route {
-                let (dx, dy) = match dir {
-                    0 => (0, -1),
-                    1 => (1, 0),
-                    2 => (0, 1),
-                    3 => (-1, 0),
-                    _ => (0, 0),
-                };
-                let new_x = (pos.0 as isize + dx).clamp(0, maze.grid()[0].len() as isize - 1) as usize;
-                let new_y = (pos.1 as isize + dy).clamp(0, maze.grid().len() as isize - 1) as usize;
-
-                if maze.grid()[new_y][new_x] != '#' {
-                    pos = (new_x, new_y);
-                    path.push(pos);
-                    if Some(pos) == maze.end_pos() {
-                        goal_reached = true;
-                        final_path = path.clone();
-                        break;
-                    }
+            let path = maze.trace_route(&best_route);
+            let pickups = maze.waypoint_pickups(&path);
+            let key_pickups = maze.key_pickups(&path);
+            let adversaries = maze.adversary_positions(&best_route);
+            if path.last().copied() == maze.end_pos() && pickups.iter().all(Option::is_some) {
+                goal_reached = true;
+                final_path = path.clone();
+                final_pickups = pickups.clone();
+                final_key_pickups = key_pickups.clone();
+                final_adversaries = adversaries.clone();
+
+                if let Some(ratio) = maze.route_optimality(&best_route) {
+                    println!("Route optimality: {ratio:.3} (1.000 is optimal)");
                 }
             }
 
-            visualizer.animate(&path).await;
+            visualizer.animate(&path, &pickups, &key_pickups, &adversaries).await;
         } else {
-            visualizer.animate(&final_path).await;
+            visualizer.animate(&final_path, &final_pickups, &final_key_pickups, &final_adversaries).await;
         }
 
         next_frame().await;
@@ -1,171 +1,331 @@
 // maze.rs
 
+use ::rand::rngs::StdRng;
 use ::rand::seq::SliceRandom;
-use ::rand::thread_rng;
-use macroquad::prelude::*;
-
-#[derive(Clone, Debug)]
-struct Cell {
-    x: usize,
-    y: usize,
-    neighbors: Vec<(usize, usize)>,
-    wall: bool,
-    open: bool,
-}
-
-impl Cell {
-    fn new(x: usize, y: usize, is_wall: bool) -> Self {
-        Self {
-            x,
-            y,
-            neighbors: Vec::new(),
-            wall: is_wall,
-            open: true,
-        }
-    }
-
-    fn add_neighbor(&mut self, neighbor: (usize, usize)) {
-        if !self.neighbors.contains(&neighbor) {
-            self.neighbors.push(neighbor);
-        }
-    }
-}
+use ::rand::{Rng, RngCore, SeedableRng, thread_rng};
+
+mod adversaries;
+mod ascii;
+mod doors;
+mod export;
+mod generators;
+mod graph;
+mod hazards;
+mod keys;
+mod levels;
+mod metrics;
+mod placement;
+mod sensors;
+mod solvers;
+mod storage;
+mod terrain;
+mod topology;
+mod validate;
+mod waypoints;
+mod wrap;
+
+pub use adversaries::{Adversary, Collision};
+pub use ascii::AsciiError;
+pub use doors::{Door, DoorChange};
+pub use export::{CellDocument, JsonError, MazeDocument};
+pub use generators::{
+    AldousBroder, BinaryTree, Dungeon, Ellers, Kruskals, MazeGenerator, Prims, RecursiveBacktracker,
+    RecursiveDivision, Sidewinder, WeightedKruskals, Wilsons,
+};
+pub use graph::{JunctionEdge, JunctionGraph};
+pub use hazards::DEFAULT_HAZARD_PENALTY;
+pub use keys::Lock;
+pub use levels::{STAIRS_BOTH, STAIRS_DOWN, STAIRS_UP};
+pub use metrics::{MazeMetrics, SOLUTION_COUNT_LIMIT, SOLUTION_SEARCH_LIMIT};
+pub use placement::{Placement, PlacementError};
+pub use sensors::{Agent, Observation, SensorConfig};
+pub use solvers::Solution;
+pub use storage::TileGrid;
+use storage::CellStore;
+pub use terrain::Terrain;
+pub use topology::Topology;
+pub use validate::Violation;
+pub use waypoints::WaypointOrder;
+
+/// A `(x, y)` coordinate in the character grid.
+///
+/// In hex and triangle mazes the grid has one character per cell, so this is also the cell.
+pub type Position = (usize, usize);
 
 pub struct Maze {
     dimension_x: usize,
     dimension_y: usize,
-    cells: Vec<Vec<Cell>>,
-    grid: Vec<Vec<char>>,
+    cells: CellStore,
+    grid: TileGrid,
     start_pos: Option<(usize, usize)>,
     end_pos: Option<(usize, usize)>,
+    seed: Option<u64>,
+    waypoints: Vec<Position>,
+    waypoint_order: WaypointOrder,
+    doors: Vec<Door>,
+    locks: Vec<Lock>,
+    hazards: Vec<Position>,
+    hazard_penalty: f64,
+    adversaries: Vec<Adversary>,
+    collision: Collision,
+    topology: Topology,
+    levels: usize,
+    wrap: bool,
 }
 
 impl Maze {
+    /// Generates a maze from a fresh random seed, which can be read back with [`Maze::seed`].
     pub fn new(dimension_x: usize, dimension_y: usize) -> Self {
-        let mut maze = Maze {
-            dimension_x,
-            dimension_y,
-            cells: vec![vec![Cell::new(0, 0, true); dimension_y]; dimension_x],
-            grid: vec![vec!['#'; dimension_x * 2 + 1]; dimension_y * 2 + 1],
-            start_pos: None,
-            end_pos: None,
-        };
-        maze.init_cells();
-        maze.generate_maze();
-        maze.update_grid();
-        maze.place_start_end();
+        Self::with_seed(dimension_x, dimension_y, thread_rng().r#gen())
+    }
+
+    /// Generates the same maze every time for a given seed.
+    pub fn with_seed(dimension_x: usize, dimension_y: usize, seed: u64) -> Self {
+        Self::with_generator(dimension_x, dimension_y, &RecursiveBacktracker, seed)
+    }
+
+    /// Generates a maze from an injected RNG. The seed is unknown, so [`Maze::seed`] returns `None`.
+    pub fn with_rng<R: RngCore>(dimension_x: usize, dimension_y: usize, rng: &mut R) -> Self {
+        Self::from_generator(dimension_x, dimension_y, &RecursiveBacktracker, rng)
+    }
+
+    /// Generates the same maze every time for a given generator and seed.
+    pub fn with_generator(dimension_x: usize, dimension_y: usize, generator: &dyn MazeGenerator, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut maze = Self::from_generator(dimension_x, dimension_y, generator, &mut rng);
+        maze.seed = Some(seed);
         maze
     }
 
-    fn init_cells(&mut self) {
-        for x in 0..self.dimension_x {
-            for y in 0..self.dimension_y {
-                self.cells[x][y] = Cell::new(x, y, true);
-            }
-        }
+    /// Generates a maze with the given algorithm, drawing randomness from an injected RNG.
+    pub fn from_generator(dimension_x: usize, dimension_y: usize, generator: &dyn MazeGenerator, rng: &mut dyn RngCore) -> Self {
+        Self::from_topology(dimension_x, dimension_y, Topology::Square, generator, rng)
     }
 
-    fn get_cell(&self, x: isize, y: isize) -> Option<&Cell> {
-        if x >= 0 && y >= 0 && (x as usize) < self.dimension_x && (y as usize) < self.dimension_y {
-            Some(&self.cells[x as usize][y as usize])
-        } else {
-            None
-        }
+    /// Generates the same maze every time for a given cell shape, generator and seed.
+    pub fn with_topology(dimension_x: usize, dimension_y: usize, topology: Topology, generator: &dyn MazeGenerator, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut maze = Self::from_topology(dimension_x, dimension_y, topology, generator, &mut rng);
+        maze.seed = Some(seed);
+        maze
     }
 
-    fn get_cell_mut(&mut self, x: isize, y: isize) -> Option<&mut Cell> {
-        if x >= 0 && y >= 0 && (x as usize) < self.dimension_x && (y as usize) < self.dimension_y {
-            Some(&mut self.cells[x as usize][y as usize])
-        } else {
-            None
-        }
+    /// Generates a maze of the given cell shape, drawing randomness from an injected RNG.
+    ///
+    /// Panics if the generator does not support the topology (see [`MazeGenerator::supports`]),
+    /// for triangle mazes one cell wide and three or more tall, which cannot be connected, or
+    /// for hex and triangle mazes of a single cell, where the start and goal would share it.
+    pub fn from_topology(
+        dimension_x: usize,
+        dimension_y: usize,
+        topology: Topology,
+        generator: &dyn MazeGenerator,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        assert!(generator.supports(topology), "generator does not support {topology:?} mazes");
+        assert!(
+            topology != Topology::Triangle || dimension_x > 1 || dimension_y < 3,
+            "a single column of triangles is not connected"
+        );
+        assert!(
+            topology == Topology::Square || dimension_x * dimension_y != 1,
+            "a single {topology:?} cell cannot hold both the start and the goal"
+        );
+
+        Self::blank(dimension_x, dimension_y, topology).carve(generator, rng)
     }
 
-    pub fn get_grid(&self) -> Vec<Vec<char>> {
-        self.grid.clone()
+    /// Generates the same multi-level maze every time for a given generator and seed.
+    pub fn with_levels(dimension_x: usize, dimension_y: usize, levels: usize, generator: &dyn MazeGenerator, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut maze = Self::from_levels(dimension_x, dimension_y, levels, generator, &mut rng);
+        maze.seed = Some(seed);
+        maze
     }
 
-    fn generate_maze(&mut self) {
-        let mut rng = thread_rng();
-        let mut stack = Vec::new();
+    /// Generates `levels` square floors of `dimension_x` by `dimension_y` cells joined by stairs,
+    /// drawing randomness from an injected RNG. See [`Maze::levels`] for the layout.
+    ///
+    /// The start is on the top floor and the goal on the ground floor. Panics if `levels` is 0
+    /// or the generator cannot carve across floors; see [`MazeGenerator::follows_neighbors`].
+    pub fn from_levels(
+        dimension_x: usize,
+        dimension_y: usize,
+        levels: usize,
+        generator: &dyn MazeGenerator,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        assert!(levels > 0, "a maze needs at least one level");
+        assert!(levels == 1 || generator.follows_neighbors(), "generator cannot carve across levels");
+
+        let mut maze = Self::blank(dimension_x * levels, dimension_y, Topology::Square);
+        maze.levels = levels;
+        maze.carve(generator, rng)
+    }
 
-        if let Some(start) = self.get_cell_mut(0, 0) {
-            start.wall = false;
-            start.open = false;
-        }
-        stack.push((0, 0));
+    /// Generates the same wrapping maze every time for a given generator and seed.
+    pub fn with_wraparound(dimension_x: usize, dimension_y: usize, generator: &dyn MazeGenerator, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut maze = Self::from_wraparound(dimension_x, dimension_y, generator, &mut rng);
+        maze.seed = Some(seed);
+        maze
+    }
 
-        while let Some((x, y)) = stack.pop() {
-            let mut neighbors = Vec::new();
+    /// Generates a square maze whose edges wrap around (see [`Maze::wraps`]), drawing
+    /// randomness from an injected RNG.
+    ///
+    /// Panics if either dimension is below 3, where the wrapped edges would double up, or if
+    /// the generator cannot carve across the edges; see [`MazeGenerator::follows_neighbors`].
+    pub fn from_wraparound(dimension_x: usize, dimension_y: usize, generator: &dyn MazeGenerator, rng: &mut dyn RngCore) -> Self {
+        assert!(dimension_x >= 3 && dimension_y >= 3, "wrapping mazes need at least 3x3 cells");
+        assert!(generator.follows_neighbors(), "generator cannot carve across the edges");
+
+        let mut maze = Self::blank(dimension_x, dimension_y, Topology::Square);
+        maze.make_wrapping();
+        maze.carve(generator, rng)
+    }
 
-            for &(dx, dy) in &[(1, 0), (0, 1), (-1, 0), (0, -1)] {
-                let nx = x as isize + dx;
-                let ny = y as isize + dy;
+    fn carve(mut self, generator: &dyn MazeGenerator, rng: &mut dyn RngCore) -> Self {
+        self.generate_maze(generator, rng);
+        self.update_grid();
+        self.place_start_end();
+        self
+    }
 
-                if let Some(cell) = self.get_cell(nx, ny) {
-                    if cell.wall && cell.open {
-                        neighbors.push((nx as usize, ny as usize));
-                    }
-                }
-            }
+    /// A maze where every cell is still a wall, with no start or goal.
+    fn blank(dimension_x: usize, dimension_y: usize, topology: Topology) -> Self {
+        let (columns, rows) = topology.grid_size(dimension_x, dimension_y);
+        Maze {
+            dimension_x,
+            dimension_y,
+            cells: CellStore::new(dimension_x, dimension_y),
+            grid: TileGrid::new(columns, rows),
+            start_pos: None,
+            end_pos: None,
+            seed: None,
+            waypoints: Vec::new(),
+            waypoint_order: WaypointOrder::default(),
+            doors: Vec::new(),
+            locks: Vec::new(),
+            hazards: Vec::new(),
+            hazard_penalty: DEFAULT_HAZARD_PENALTY,
+            adversaries: Vec::new(),
+            collision: Collision::default(),
+            topology,
+            levels: 1,
+            wrap: false,
+        }
+    }
 
-            if neighbors.is_empty() {
-                continue;
-            }
+    pub fn get_grid(&self) -> TileGrid {
+        self.grid.clone()
+    }
 
-            stack.push((x, y));
+    fn generate_maze(&mut self, generator: &dyn MazeGenerator, rng: &mut dyn RngCore) {
+        generator.generate(self, rng);
+    }
 
-            let &(nx, ny) = neighbors.choose(&mut rng).unwrap();
+    /// In-bounds neighbours of a cell. Square cells list them in the order right, down, left, up,
+    /// then the cells above and below on multi-level mazes; other topologies use their direction order.
+    fn adjacent(&self, cell: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (count, turn) = match self.topology {
+            Topology::Square => (6, 1),
+            topology => (topology.directions(), 0),
+        };
 
-            {
-                {
-                    let current = self.get_cell_mut(x as isize, y as isize).unwrap();
-                    current.add_neighbor((nx, ny));
-                    current.wall = false;
-                }
+        (0..count)
+            .map(move |i| if i < 4 { (i + turn) % 4 } else { i })
+            .filter_map(move |direction| self.cell_in_direction(cell, direction))
+    }
 
-                let selected = self.get_cell_mut(nx as isize, ny as isize).unwrap();
-                selected.add_neighbor((x, y));
-                selected.wall = false;
-                selected.open = false;
-            }
+    /// Opens a passage between two adjacent cells, marking both as no longer solid wall.
+    fn link(&mut self, a: (usize, usize), b: (usize, usize)) {
+        let (forward, back) = self.link_directions(a, b);
+        self.cells.set_link(a, forward, true);
+        self.cells.set_wall(a, false);
+        self.cells.set_link(b, back, true);
+        self.cells.set_wall(b, false);
+    }
 
-            stack.push((nx, ny));
+    /// Closes a passage previously opened with [`Maze::link`].
+    fn unlink(&mut self, a: (usize, usize), b: (usize, usize)) {
+        let (forward, back) = self.link_directions(a, b);
+        self.cells.set_link(a, forward, false);
+        self.cells.set_link(b, back, false);
+    }
+
+    /// The directions from `a` to `b` and back. Panics if the cells are not adjacent.
+    fn link_directions(&self, a: (usize, usize), b: (usize, usize)) -> (usize, usize) {
+        match (self.direction_to(a, b), self.direction_to(b, a)) {
+            (Some(forward), Some(back)) => (forward, back),
+            _ => panic!("cells {a:?} and {b:?} are not adjacent"),
         }
     }
 
     fn update_grid(&mut self) {
-        for row in self.grid.iter_mut() {
-            for ch in row.iter_mut() {
-                *ch = '#';
-            }
-        }
+        self.grid = self.render_grid();
+    }
+
+    /// The character grid as drawn from the cell graph alone, without start, goal or terrain.
+    fn render_grid(&self) -> TileGrid {
+        let mut grid = TileGrid::new(self.grid.width(), self.grid.height());
 
         for x in 0..self.dimension_x {
             for y in 0..self.dimension_y {
+                let open = !self.cells.is_wall((x, y));
+                if self.topology != Topology::Square {
+                    if open {
+                        grid.set((x, y), ' ');
+                    }
+                    continue;
+                }
+
                 let grid_x = x * 2 + 1;
                 let grid_y = y * 2 + 1;
 
-                if !self.cells[x][y].wall {
-                    self.grid[grid_y][grid_x] = ' ';
+                if open {
+                    grid.set((grid_x, grid_y), self.stair_symbol((x, y)).unwrap_or(' '));
                 }
 
-                for &(nx, ny) in &self.cells[x][y].neighbors {
-                    let passage_x = (grid_x + (nx * 2 + 1)) / 2;
-                    let passage_y = (grid_y + (ny * 2 + 1)) / 2;
-                    self.grid[passage_y][passage_x] = ' ';
+                for (nx, ny) in self.cell_neighbors((x, y)) {
+                    if self.cell_level((nx, ny)) != self.cell_level((x, y)) {
+                        continue;
+                    }
+
+                    // Cells more than one apart are joined across the edge of a wrapping maze.
+                    let passage_x = if nx.abs_diff(x) > 1 { 0 } else { (grid_x + (nx * 2 + 1)) / 2 };
+                    let passage_y = if ny.abs_diff(y) > 1 { 0 } else { (grid_y + (ny * 2 + 1)) / 2 };
+                    grid.set((passage_x, passage_y), ' ');
                 }
             }
         }
+
+        grid
     }
 
     fn place_start_end(&mut self) {
+        // Opposite edges of a wrapping maze touch, so the goal goes halfway round instead.
+        if self.wrap {
+            self.start_pos = Some(self.cell_to_tile((0, 0)));
+            self.end_pos = Some(self.cell_to_tile((self.dimension_x / 2, self.dimension_y / 2)));
+            self.mark_start_end();
+            return;
+        }
+
+        if self.topology != Topology::Square {
+            let right = self.dimension_x.saturating_sub(1);
+            self.start_pos = (0..self.dimension_y).map(|y| (right, y)).find(|&pos| self.grid.is_open(pos));
+            self.end_pos = (0..self.dimension_y).rev().map(|y| (0, y)).find(|&pos| self.grid.is_open(pos));
+            self.mark_start_end();
+            return;
+        }
+
         for y in 0..self.dimension_y {
             let grid_x = self.dimension_x * 2;
             let grid_y = y * 2 + 1;
 
-            if self.grid[grid_y][grid_x - 1] == ' ' {
-                self.grid[grid_y][grid_x] = '*';
+            if self.grid.is_open((grid_x - 1, grid_y)) {
+                self.grid.set((grid_x, grid_y), '*');
                 self.start_pos = Some((grid_x, grid_y));
                 break;
             }
@@ -175,17 +335,77 @@ impl Maze {
             let grid_x = 0;
             let grid_y = y * 2 + 1;
 
-            if self.grid[grid_y][grid_x + 1] == ' ' {
-                self.grid[grid_y][grid_x] = '~';
+            if self.grid.is_open((grid_x + 1, grid_y)) {
+                self.grid.set((grid_x, grid_y), '~');
                 self.end_pos = Some((grid_x, grid_y));
                 break;
             }
         }
     }
 
+    /// Removes roughly `factor` (0.0 to 1.0) of the dead ends by opening a passage into an
+    /// adjacent cell, turning the perfect maze into one with loops and several routes to the goal.
+    /// Dead ends are joined to each other where possible so one opening removes two of them.
+    /// Returns the number of passages opened.
+    pub fn braid(&mut self, factor: f64, rng: &mut dyn RngCore) -> usize {
+        let mut dead_ends: Vec<Position> = self.dead_ends().collect();
+        dead_ends.shuffle(rng);
+
+        let target = (dead_ends.len() as f64 * factor.clamp(0.0, 1.0)).round() as usize;
+        let mut opened = 0;
+
+        for &cell in dead_ends.iter().take(target) {
+            if self.degree(cell) != 1 {
+                continue;
+            }
+
+            let candidates: Vec<(usize, usize)> = self
+                .adjacent(cell)
+                .filter(|&n| !self.is_linked(cell, n))
+                .collect();
+            let paired: Vec<(usize, usize)> = candidates
+                .iter()
+                .copied()
+                .filter(|&n| self.degree(n) == 1)
+                .collect();
+
+            let choice = if paired.is_empty() { candidates.choose(rng) } else { paired.choose(rng) };
+            if let Some(&next) = choice {
+                self.link(cell, next);
+                opened += 1;
+            }
+        }
+
+        self.rebuild_grid();
+        opened
+    }
+
+    /// Redraws the grid from the cell graph, keeping terrain on tiles that are still open.
+    fn rebuild_grid(&mut self) {
+        let previous = self.grid.clone();
+        self.update_grid();
+
+        for (pos, old) in previous.marked() {
+            if self.grid[pos] == ' ' && Terrain::from_symbol(old).is_some() {
+                self.grid.set(pos, old);
+            }
+        }
+
+        self.mark_start_end();
+    }
+
+    fn mark_start_end(&mut self) {
+        if let Some(pos) = self.start_pos {
+            self.grid.set(pos, '*');
+        }
+        if let Some(pos) = self.end_pos {
+            self.grid.set(pos, '~');
+        }
+    }
+
     pub fn _display(&self) {
-        for row in &self.grid {
-            let line: String = row.iter().collect();
+        for y in 0..self.grid.height() {
+            let line: String = self.grid.row(y).collect();
             println!("{line}");
         }
     }
@@ -195,29 +415,47 @@ impl Maze {
         let mut visited = std::collections::HashSet::new();
         visited.insert(pos);
 
+        let mut collected = vec![false; self.waypoints.len()];
+        let mut keys = vec![false; self.locks.len()];
+        let mut adversaries = self.start_adversaries();
+
         let mut fitness: f64 = 0.0;
-        let mut steps = 0;
+        let mut cost = 0.0;
         let mut reached_goal = false;
 
         for dir in route {
-            let (dx, dy) = match dir {
-                0 => (0, -1),
-                1 => (1, 0),
-                2 => (0, 1),
-                3 => (-1, 0),
-                _ => (0, 0),
-            };
-
-            let new_x = (pos.0 as isize + dx) as usize;
-            let new_y = (pos.1 as isize + dy) as usize;
+            let next = self.step(pos, dir).filter(|&next| !self.is_locked(next, &keys));
+
+            if !self.adversaries.is_empty() {
+                let to = next.unwrap_or(pos);
+                self.advance_adversaries(&mut adversaries, to);
+                if self.collides(&adversaries, pos, to) {
+                    match self.collision {
+                        Collision::Penalty(penalty) => fitness -= penalty,
+                        Collision::EndRoute(penalty) => {
+                            fitness -= penalty;
+                            pos = to;
+                            break;
+                        }
+                    }
+                }
+            }
 
-            if new_x >= self.grid[0].len() || new_y >= self.grid.len() || self.grid[new_y][new_x] == '#' {
+            let Some(next) = next else {
                 fitness -= 25.0;
                 continue;
+            };
+
+            pos = next;
+
+            if self.is_hazard(pos) {
+                fitness -= self.hazard_penalty;
+                break;
             }
 
-            pos = (new_x, new_y);
-            steps += 1;
+            let step_cost = self.step_cost(pos).unwrap_or(1.0);
+            cost += step_cost;
+            fitness -= step_cost - 1.0;
 
             if visited.insert(pos) {
                 fitness += 1.0;
@@ -225,28 +463,85 @@ impl Maze {
                 fitness -= 2.5;
             }
 
-            if Some(pos) == self.end_pos() {
+            if let Some(i) = self.collectable_waypoint(pos, &collected) {
+                collected[i] = true;
+                fitness += 250.0;
+            }
+
+            if self.pick_up_key(pos, &mut keys) {
+                fitness += 100.0;
+            }
+
+            if Some(pos) == self.end_pos() && collected.iter().all(|&done| done) {
                 fitness += 1000.0;
                 reached_goal = true;
                 break;
             }
         }
 
-        if let Some(end) = self.end_pos() {
-            let dx = (end.0 as isize - pos.0 as isize).abs() as f64;
-            let dy = (end.1 as isize - pos.1 as isize).abs() as f64;
-            let manhattan_distance = dx + dy;
+        if let Some(end) = self.next_target(pos, &collected) {
+            let manhattan_distance = self.grid_distance(pos, end) as f64;
 
             fitness += 50.0 / (1.0 + manhattan_distance);
 
             if reached_goal {
-                fitness += 100.0 / (1.0 + steps as f64);
+                fitness += 100.0 / (1.0 + cost);
             }
         }
 
         fitness
     }
 
+    /// The tile a route is on after each move, starting with the start tile and stopping on a
+    /// hazard, on a collision that ends the route, or once the goal is reached with every
+    /// waypoint collected, as [`Maze::test_route`] does. A move into a wall or locked door
+    /// repeats the current tile, so the path keeps time with the adversaries.
+    pub fn trace_route(&self, route: &[u8]) -> Vec<Position> {
+        self.trace(route).0
+    }
+
+    /// The path of [`Maze::trace_route`] together with where the adversaries are at each step.
+    fn trace(&self, route: &[u8]) -> (Vec<Position>, Vec<Vec<Position>>) {
+        let Some(mut pos) = self.start_pos else {
+            return (Vec::new(), Vec::new());
+        };
+        let mut path = vec![pos];
+        let mut collected = vec![false; self.waypoints.len()];
+        let mut keys = vec![false; self.locks.len()];
+        let mut adversaries = self.start_adversaries();
+        let mut frames = vec![adversaries.positions().to_vec()];
+
+        for &dir in route {
+            let next = self.step(pos, dir).filter(|&next| !self.is_locked(next, &keys));
+            let to = next.unwrap_or(pos);
+            self.advance_adversaries(&mut adversaries, to);
+            let caught = matches!(self.collision, Collision::EndRoute(_)) && self.collides(&adversaries, pos, to);
+
+            pos = to;
+            path.push(pos);
+            frames.push(adversaries.positions().to_vec());
+            if caught {
+                break;
+            }
+            if next.is_none() {
+                continue;
+            }
+            if self.is_hazard(pos) {
+                break;
+            }
+            self.pick_up_key(pos, &mut keys);
+
+            if let Some(i) = self.collectable_waypoint(pos, &collected) {
+                collected[i] = true;
+            }
+            if Some(pos) == self.end_pos && collected.iter().all(|&done| done) {
+                break;
+            }
+        }
+
+        (path, frames)
+    }
+
     pub fn start_pos(&self) -> Option<(usize, usize)> {
         self.start_pos
     }
@@ -255,8 +550,12 @@ impl Maze {
         self.end_pos
     }
 
-    pub fn grid(&self) -> &Vec<Vec<char>> {
+    pub fn grid(&self) -> &TileGrid {
         &self.grid
     }
 
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
 }
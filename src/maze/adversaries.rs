@@ -0,0 +1,207 @@
+//adversaries.rs
+
+use super::{Maze, PlacementError, Position};
+use ::rand::seq::SliceRandom;
+use ::rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// An agent that moves through the maze while a route is tested, one tick per gene.
+///
+/// Adversaries walk open tiles only and ignore locked doors and hazards. The solvers do not
+/// know about them, so optimal routes are optimal for the empty maze.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Adversary {
+    /// Walks a closed loop of tiles, one tile per tick, starting on the first. Each tile must be
+    /// one move from the one before it, and the last one move from the first.
+    Patrol { route: Vec<Position> },
+    /// Starts on `start` and every `period` ticks steps to the neighbouring tile nearest the
+    /// route, never turning back unless it is in a dead end. A period of 0 counts as 1.
+    Chaser { start: Position, period: usize },
+}
+
+/// What happens when a route meets an adversary on a tile or passes one head-on.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Collision {
+    /// Loses this much fitness on every tick spent in contact and carries on.
+    Penalty(f64),
+    /// Loses this much fitness and stops, like stepping on a hazard.
+    EndRoute(f64),
+}
+
+impl Default for Collision {
+    fn default() -> Self {
+        Collision::Penalty(50.0)
+    }
+}
+
+/// Where the adversaries are during one route simulation.
+pub(super) struct AdversaryState {
+    tick: usize,
+    positions: Vec<Position>,
+    before: Vec<Position>,
+    came_from: Vec<Option<Position>>,
+}
+
+impl AdversaryState {
+    pub(super) fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+}
+
+impl Maze {
+    /// Adds an adversary, checking that it starts on an open tile and that a patrol loop only
+    /// makes single moves between open tiles.
+    pub fn add_adversary(&mut self, adversary: Adversary) -> Result<(), PlacementError> {
+        let tiles = match &adversary {
+            Adversary::Patrol { route } if route.is_empty() => return Err(PlacementError::NoOpenTiles),
+            Adversary::Patrol { route } => route.clone(),
+            Adversary::Chaser { start, .. } => vec![*start],
+        };
+
+        for &(x, y) in &tiles {
+            if y >= self.grid.height() || x >= self.grid.width() {
+                return Err(PlacementError::OutOfBounds((x, y)));
+            }
+            if self.grid[(x, y)] == '#' {
+                return Err(PlacementError::Blocked((x, y)));
+            }
+        }
+        for (i, &tile) in tiles.iter().enumerate() {
+            let next = tiles[(i + 1) % tiles.len()];
+            if next != tile && !self.open_neighbors(tile).contains(&next) {
+                return Err(PlacementError::Blocked(next));
+            }
+        }
+
+        self.adversaries.push(adversary);
+        Ok(())
+    }
+
+    /// Adds up to `count` patrols, each pacing back and forth along a random walk of up to
+    /// `length` tiles that avoids the start. Returns the number of patrols added.
+    pub fn add_random_patrols(&mut self, count: usize, length: usize, rng: &mut dyn RngCore) -> usize {
+        let open: Vec<Position> = self.open_tiles().collect();
+        let mut added = 0;
+
+        for _ in 0..count {
+            let Some(&first) = open.choose(rng) else {
+                break;
+            };
+
+            let mut walk = vec![first];
+            while walk.len() < length.max(1) {
+                let last = walk[walk.len() - 1];
+                let onward: Vec<Position> = self
+                    .open_neighbors(last)
+                    .into_iter()
+                    .filter(|&next| !walk.contains(&next) && Some(next) != self.start_pos)
+                    .collect();
+                let Some(&next) = onward.choose(rng) else {
+                    break;
+                };
+                walk.push(next);
+            }
+
+            let mut route = walk.clone();
+            route.extend(walk.iter().rev().skip(1).take(walk.len().saturating_sub(2)));
+            if self.add_adversary(Adversary::Patrol { route }).is_ok() {
+                added += 1;
+            }
+        }
+
+        added
+    }
+
+    /// Adds up to `count` chasers on random open tiles at least a quarter of the maze away
+    /// from the start. Returns the number of chasers added.
+    pub fn add_random_chasers(&mut self, count: usize, period: usize, rng: &mut dyn RngCore) -> usize {
+        let Some(start) = self.start_pos else {
+            return 0;
+        };
+        let min_distance = (self.grid.height() + self.grid.width()) / 4;
+        let mut candidates: Vec<Position> = self
+            .open_tiles()
+            .filter(|&pos| self.grid_distance(pos, start) >= min_distance)
+            .collect();
+        candidates.shuffle(rng);
+
+        let mut added = 0;
+        for start in candidates.into_iter().take(count) {
+            if self.add_adversary(Adversary::Chaser { start, period }).is_ok() {
+                added += 1;
+            }
+        }
+
+        added
+    }
+
+    pub fn adversaries(&self) -> &[Adversary] {
+        &self.adversaries
+    }
+
+    pub fn collision(&self) -> Collision {
+        self.collision
+    }
+
+    pub fn set_collision(&mut self, collision: Collision) {
+        self.collision = collision;
+    }
+
+    /// Where the adversaries are at each position of [`Maze::trace_route`]'s path, starting
+    /// with where they are before the first move.
+    pub fn adversary_positions(&self, route: &[u8]) -> Vec<Vec<Position>> {
+        self.trace(route).1
+    }
+
+    pub(super) fn start_adversaries(&self) -> AdversaryState {
+        let positions: Vec<Position> = self
+            .adversaries
+            .iter()
+            .map(|adversary| match adversary {
+                Adversary::Patrol { route } => route[0],
+                Adversary::Chaser { start, .. } => *start,
+            })
+            .collect();
+
+        AdversaryState {
+            tick: 0,
+            before: positions.clone(),
+            came_from: vec![None; positions.len()],
+            positions,
+        }
+    }
+
+    /// Moves every adversary one tick, with chasers heading for `target`.
+    pub(super) fn advance_adversaries(&self, state: &mut AdversaryState, target: Position) {
+        state.tick += 1;
+        state.before.clone_from(&state.positions);
+
+        for (i, adversary) in self.adversaries.iter().enumerate() {
+            let pos = state.positions[i];
+            match adversary {
+                Adversary::Patrol { route } => state.positions[i] = route[state.tick % route.len()],
+                Adversary::Chaser { period, .. } if state.tick.is_multiple_of((*period).max(1)) => {
+                    let neighbors = self.open_neighbors(pos);
+                    let forward: Vec<Position> = neighbors.iter().copied().filter(|&n| Some(n) != state.came_from[i]).collect();
+                    let choices = if forward.is_empty() { neighbors } else { forward };
+
+                    if let Some(next) = choices.into_iter().min_by_key(|&n| self.grid_distance(n, target)) {
+                        state.came_from[i] = Some(pos);
+                        state.positions[i] = next;
+                    }
+                }
+                Adversary::Chaser { .. } => {}
+            }
+        }
+    }
+
+    /// Whether a route moving from `from` to `to` this tick meets an adversary, either on `to`
+    /// or by swapping tiles with it.
+    pub(super) fn collides(&self, state: &AdversaryState, from: Position, to: Position) -> bool {
+        state
+            .positions
+            .iter()
+            .zip(&state.before)
+            .any(|(&now, &before)| now == to || (now == from && before == to))
+    }
+}
@@ -0,0 +1,223 @@
+//ascii.rs
+
+use super::{Maze, Position, Terrain, TileGrid, Topology};
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum AsciiError {
+    Io(std::io::Error),
+    Empty,
+    RaggedRow { row: usize, expected: usize, found: usize },
+    BadDimensions { width: usize, height: usize },
+    UnknownChar { row: usize, col: usize, ch: char },
+    MissingStart,
+    MissingGoal,
+    DuplicateStart { first: Position, second: Position },
+    DuplicateGoal { first: Position, second: Position },
+}
+
+impl fmt::Display for AsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsciiError::Io(err) => write!(f, "could not read maze file: {err}"),
+            AsciiError::Empty => write!(f, "maze text is empty"),
+            AsciiError::RaggedRow { row, expected, found } => {
+                write!(f, "row {row} has {found} characters, expected {expected}")
+            }
+            AsciiError::BadDimensions { width, height } => {
+                write!(f, "grid is {width}x{height}, but both sides must be odd (2 * cells + 1)")
+            }
+            AsciiError::UnknownChar { row, col, ch } => write!(f, "unknown character {ch:?} at row {row}, column {col}"),
+            AsciiError::MissingStart => write!(f, "no start marker '*' found"),
+            AsciiError::MissingGoal => write!(f, "no goal marker '~' found"),
+            AsciiError::DuplicateStart { first, second } => {
+                write!(f, "start marker '*' appears at both {first:?} and {second:?}")
+            }
+            AsciiError::DuplicateGoal { first, second } => {
+                write!(f, "goal marker '~' appears at both {first:?} and {second:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsciiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AsciiError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for AsciiError {
+    fn from(err: std::io::Error) -> Self {
+        AsciiError::Io(err)
+    }
+}
+
+impl Maze {
+    /// Parses a maze in the format printed by `_display`: '#' walls, ' ' open tiles,
+    /// '*' the start and '~' the goal, with exactly one of each marker. Terrain symbols
+    /// ('.' road, ',' mud, '=' water) are open tiles with a movement cost.
+    ///
+    /// The grid must be `2 * cells + 1` characters on each side. Rows and columns are reported
+    /// zero-based in errors. Parsing does not check that every cell or the goal can be reached;
+    /// use [`Maze::validate`] for that.
+    pub fn from_ascii(text: &str) -> Result<Self, AsciiError> {
+        let grid: Vec<Vec<char>> = text
+            .lines()
+            .map(|line| line.trim_end_matches('\r').chars().collect())
+            .collect();
+
+        if grid.is_empty() || grid[0].is_empty() {
+            return Err(AsciiError::Empty);
+        }
+
+        let width = grid[0].len();
+        let height = grid.len();
+        let mut start_pos = None;
+        let mut end_pos = None;
+
+        for (y, row) in grid.iter().enumerate() {
+            if row.len() != width {
+                return Err(AsciiError::RaggedRow { row: y, expected: width, found: row.len() });
+            }
+
+            for (x, &ch) in row.iter().enumerate() {
+                match ch {
+                    '#' => {}
+                    _ if Terrain::from_symbol(ch).is_some() => {}
+                    '*' => match start_pos {
+                        Some(first) => return Err(AsciiError::DuplicateStart { first, second: (x, y) }),
+                        None => start_pos = Some((x, y)),
+                    },
+                    '~' => match end_pos {
+                        Some(first) => return Err(AsciiError::DuplicateGoal { first, second: (x, y) }),
+                        None => end_pos = Some((x, y)),
+                    },
+                    _ => return Err(AsciiError::UnknownChar { row: y, col: x, ch }),
+                }
+            }
+        }
+
+        if width.is_multiple_of(2) || height.is_multiple_of(2) || width < 3 || height < 3 {
+            return Err(AsciiError::BadDimensions { width, height });
+        }
+        let start_pos = start_pos.ok_or(AsciiError::MissingStart)?;
+        let end_pos = end_pos.ok_or(AsciiError::MissingGoal)?;
+
+        let mut maze = Maze::blank((width - 1) / 2, (height - 1) / 2, Topology::Square);
+
+        for x in 0..maze.dimension_x {
+            for y in 0..maze.dimension_y {
+                let (grid_x, grid_y) = (x * 2 + 1, y * 2 + 1);
+                if grid[grid_y][grid_x] == '#' {
+                    continue;
+                }
+
+                maze.cells.set_wall((x, y), false);
+
+                if x + 1 < maze.dimension_x && grid[grid_y][grid_x + 1] != '#' && grid[grid_y][grid_x + 2] != '#' {
+                    maze.link((x, y), (x + 1, y));
+                }
+                if y + 1 < maze.dimension_y && grid[grid_y + 1][grid_x] != '#' && grid[grid_y + 2][grid_x] != '#' {
+                    maze.link((x, y), (x, y + 1));
+                }
+            }
+        }
+
+        maze.grid = TileGrid::from_rows(&grid);
+        maze.start_pos = Some(start_pos);
+        maze.end_pos = Some(end_pos);
+        Ok(maze)
+    }
+
+    /// Reads a maze file in the [`Maze::from_ascii`] format.
+    pub fn load_ascii(path: impl AsRef<Path>) -> Result<Self, AsciiError> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_ascii(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: &str = "#####\n*   #\n### #\n~   #\n#####\n";
+
+    #[test]
+    fn parses_a_small_maze() {
+        let maze = Maze::from_ascii(SMALL).unwrap();
+        assert_eq!((maze.dimension_x, maze.dimension_y), (2, 2));
+        assert_eq!(maze.start_pos(), Some((0, 1)));
+        assert_eq!(maze.end_pos(), Some((0, 3)));
+        assert!(maze.is_linked((1, 0), (1, 1)));
+        assert!(!maze.is_linked((0, 0), (0, 1)));
+        assert_eq!(maze.to_ascii(), SMALL);
+    }
+
+    #[test]
+    fn accepts_crlf_line_endings() {
+        let maze = Maze::from_ascii(&SMALL.replace('\n', "\r\n")).unwrap();
+        assert_eq!(maze.to_ascii(), SMALL);
+    }
+
+    #[test]
+    fn round_trips_a_generated_maze() {
+        let maze = Maze::with_seed(7, 5, 42);
+        assert_eq!(Maze::from_ascii(&maze.to_ascii()).unwrap().to_ascii(), maze.to_ascii());
+    }
+
+    #[test]
+    fn rejects_empty_text() {
+        assert!(matches!(Maze::from_ascii(""), Err(AsciiError::Empty)));
+        assert!(matches!(Maze::from_ascii("\n"), Err(AsciiError::Empty)));
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        let text = "#####\n*   #\n### \n~   #\n#####\n";
+        assert!(matches!(
+            Maze::from_ascii(text),
+            Err(AsciiError::RaggedRow { row: 2, expected: 5, found: 4 })
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_characters() {
+        let text = "#####\n*   #\n###x#\n~   #\n#####\n";
+        assert!(matches!(
+            Maze::from_ascii(text),
+            Err(AsciiError::UnknownChar { row: 2, col: 3, ch: 'x' })
+        ));
+    }
+
+    #[test]
+    fn rejects_even_or_tiny_dimensions() {
+        let even = "######\n*    #\n#### #\n~    #\n######\n";
+        assert!(matches!(Maze::from_ascii(even), Err(AsciiError::BadDimensions { width: 6, height: 5 })));
+        assert!(matches!(Maze::from_ascii("*~\n"), Err(AsciiError::BadDimensions { width: 2, height: 1 })));
+    }
+
+    #[test]
+    fn rejects_missing_markers() {
+        assert!(matches!(Maze::from_ascii(&SMALL.replace('*', "#")), Err(AsciiError::MissingStart)));
+        assert!(matches!(Maze::from_ascii(&SMALL.replace('~', "#")), Err(AsciiError::MissingGoal)));
+    }
+
+    #[test]
+    fn rejects_duplicate_markers() {
+        let two_starts = "#####\n*   *\n### #\n~   #\n#####\n";
+        assert!(matches!(
+            Maze::from_ascii(two_starts),
+            Err(AsciiError::DuplicateStart { first: (0, 1), second: (4, 1) })
+        ));
+
+        let two_goals = "#####\n*   #\n### #\n~   ~\n#####\n";
+        assert!(matches!(
+            Maze::from_ascii(two_goals),
+            Err(AsciiError::DuplicateGoal { first: (0, 3), second: (4, 3) })
+        ));
+    }
+}
@@ -0,0 +1,211 @@
+//doors.rs
+
+use super::{Maze, PlacementError, Position, Topology};
+use ::rand::seq::SliceRandom;
+use ::rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// A passage tile that opens and closes on a generation schedule.
+///
+/// The door is open while `(generation + phase) % period < open_for`. A `period` of 0 keeps it open.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Door {
+    pub position: Position,
+    pub period: usize,
+    pub open_for: usize,
+    pub phase: usize,
+}
+
+impl Door {
+    pub fn is_open_at(&self, generation: usize) -> bool {
+        self.period == 0 || (generation + self.phase) % self.period < self.open_for
+    }
+}
+
+/// Doors toggled by one call to [`Maze::update_doors`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DoorChange {
+    pub opened: Vec<Position>,
+    pub closed: Vec<Position>,
+}
+
+impl DoorChange {
+    pub fn is_empty(&self) -> bool {
+        self.opened.is_empty() && self.closed.is_empty()
+    }
+}
+
+impl Maze {
+    /// Adds a scheduled door on an open passage tile between two cells.
+    ///
+    /// The door is rejected with [`PlacementError::Blocked`] if closing it together with every
+    /// other door would leave no route to the goal with every waypoint that avoids hazards and
+    /// locked doors, so the maze stays solvable whatever the schedule. Doors need passage tiles, so only square mazes have them.
+    /// A tile painted with terrain counts as occupied, since the door would clear it to `' '`
+    /// each time it opens.
+    pub fn add_door(&mut self, door: Door) -> Result<(), PlacementError> {
+        let (x, y) = door.position;
+        if y >= self.grid.height() || x >= self.grid.width() {
+            return Err(PlacementError::OutOfBounds((x, y)));
+        }
+        if self.topology != Topology::Square || self.is_border_tile((x, y)) || self.grid[(x, y)] == '#' || self.door_cells((x, y)).is_none() {
+            return Err(PlacementError::Blocked((x, y)));
+        }
+        if self.grid[(x, y)] != ' '
+            || self.doors.iter().any(|existing| existing.position == (x, y))
+            || self.locks.iter().any(|lock| lock.key == (x, y) || lock.door == (x, y))
+            || self.hazards.contains(&(x, y))
+        {
+            return Err(PlacementError::Occupied((x, y)));
+        }
+
+        self.doors.push(door);
+        if !self.solvable_with_doors_closed() {
+            self.doors.pop();
+            return Err(PlacementError::Blocked((x, y)));
+        }
+
+        Ok(())
+    }
+
+    /// Tries up to every open passage tile in random order and adds at most `count` doors
+    /// with the given schedule and a random phase. Returns the number of doors added.
+    pub fn add_random_doors(&mut self, count: usize, period: usize, open_for: usize, rng: &mut dyn RngCore) -> usize {
+        let mut candidates: Vec<Position> = (1..self.grid.height() - 1)
+            .flat_map(|y| (1..self.grid.width() - 1).map(move |x| (x, y)))
+            .filter(|&(x, y)| (x + y) % 2 == 1 && self.grid[(x, y)] == ' ')
+            .collect();
+        candidates.shuffle(rng);
+
+        let mut added = 0;
+        for position in candidates {
+            if added == count {
+                break;
+            }
+
+            let phase = if period == 0 { 0 } else { (rng.next_u64() % period as u64) as usize };
+            if self.add_door(Door { position, period, open_for, phase }).is_ok() {
+                added += 1;
+            }
+        }
+
+        added
+    }
+
+    pub fn doors(&self) -> &[Door] {
+        &self.doors
+    }
+
+    /// Opens and closes doors to match their schedule at `generation`, keeping the grid and
+    /// the cell graph in step. Returns the doors that changed state. A door that is not on a
+    /// passage tile, which only a hand-edited maze can hold, is left alone.
+    pub fn update_doors(&mut self, generation: usize) -> DoorChange {
+        let mut change = DoorChange::default();
+
+        for i in 0..self.doors.len() {
+            let (x, y) = self.doors[i].position;
+            let Some((a, b)) = self.door_cells((x, y)) else {
+                continue;
+            };
+            let is_open = self.grid[(x, y)] != '#';
+
+            match (self.doors[i].is_open_at(generation), is_open) {
+                (true, false) => {
+                    self.set_door_open((x, y), (a, b), true);
+                    change.opened.push((x, y));
+                }
+                (false, true) => {
+                    self.set_door_open((x, y), (a, b), false);
+                    change.closed.push((x, y));
+                }
+                _ => {}
+            }
+        }
+
+        change
+    }
+
+    /// The two cells a passage tile joins, or `None` if `pos` is not a passage tile between
+    /// two cells of a square maze.
+    pub(super) fn door_cells(&self, (x, y): Position) -> Option<(Position, Position)> {
+        if self.topology != Topology::Square || x / 2 >= self.dimension_x || y / 2 >= self.dimension_y {
+            return None;
+        }
+        match (x % 2, y % 2) {
+            (0, 1) if x > 0 => Some(((x / 2 - 1, y / 2), (x / 2, y / 2))),
+            (1, 0) if y > 0 => Some(((x / 2, y / 2 - 1), (x / 2, y / 2))),
+            _ => None,
+        }
+    }
+
+    /// Whether [`Maze::solve_bfs`] still finds a route with every scheduled door closed. Open
+    /// doors only add routes, so the maze is then solvable at every generation.
+    pub(super) fn solvable_with_doors_closed(&mut self) -> bool {
+        let open: Vec<(Position, (Position, Position))> = self
+            .doors
+            .iter()
+            .filter(|door| self.grid.is_open(door.position))
+            .filter_map(|door| Some((door.position, self.door_cells(door.position)?)))
+            .collect();
+
+        for &(pos, cells) in &open {
+            self.set_door_open(pos, cells, false);
+        }
+        let solvable = self.solve_bfs().is_some();
+        for &(pos, cells) in &open {
+            self.set_door_open(pos, cells, true);
+        }
+
+        solvable
+    }
+
+    fn set_door_open(&mut self, pos: Position, (a, b): (Position, Position), open: bool) {
+        if open {
+            self.grid.set(pos, ' ');
+            self.link(a, b);
+        } else {
+            self.grid.set(pos, '#');
+            self.unlink(a, b);
+        }
+    }
+
+    /// Which grid tiles a route from the start can reach with `blocked` closed, indexed `[y][x]`.
+    pub(super) fn reachable_tiles(&self, blocked: &[Position]) -> Option<Vec<Vec<bool>>> {
+        let start = self.start_pos?;
+        let mut seen = vec![vec![false; self.grid.width()]; self.grid.height()];
+        let mut queue = VecDeque::from([start]);
+        seen[start.1][start.0] = true;
+
+        while let Some(pos) = queue.pop_front() {
+            for next in self.open_neighbors(pos) {
+                if !seen[next.1][next.0] && !blocked.contains(&next) {
+                    seen[next.1][next.0] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Some(seen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_doors_skips_doors_off_passage_tiles() {
+        let mut maze = Maze::with_seed(4, 4, 3);
+        let grid = maze.get_grid();
+        maze.doors = vec![
+            Door { position: (1, 1), period: 2, open_for: 1, phase: 0 },
+            Door { position: (40, 3), period: 2, open_for: 1, phase: 0 },
+        ];
+
+        for generation in 0..4 {
+            assert!(maze.update_doors(generation).is_empty());
+        }
+        assert_eq!(maze.get_grid(), grid);
+    }
+}
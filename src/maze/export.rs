@@ -0,0 +1,460 @@
+//export.rs
+
+use super::{Adversary, Collision, DEFAULT_HAZARD_PENALTY, Door, Lock, Maze, PlacementError, Position, STAIRS_BOTH, STAIRS_DOWN, STAIRS_UP, Terrain, TileGrid, Topology, WaypointOrder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Write as _;
+use std::path::Path;
+
+const SVG_TILE: usize = 10;
+
+/// Scales a drawing-unit coordinate to SVG pixels, rounded to keep the output readable.
+fn svg_coord(value: f32) -> f32 {
+    (value * SVG_TILE as f32 * 100.0).round() / 100.0
+}
+
+/// The JSON form of a [`Maze`].
+///
+/// `cells` carries the passage graph in cell coordinates; `grid` carries the character grid
+/// in grid coordinates, which is what routes are tested against. An empty `grid` is rebuilt
+/// from `cells` on import. Multi-level mazes keep their floors side by side, so `width` covers
+/// every floor.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MazeDocument {
+    pub width: usize,
+    pub height: usize,
+    #[serde(default)]
+    pub topology: Topology,
+    #[serde(default = "one_level")]
+    pub levels: usize,
+    #[serde(default)]
+    pub wrap: bool,
+    pub seed: Option<u64>,
+    pub start: Option<Position>,
+    pub goal: Option<Position>,
+    #[serde(default)]
+    pub waypoints: Vec<Position>,
+    #[serde(default)]
+    pub waypoint_order: WaypointOrder,
+    #[serde(default)]
+    pub doors: Vec<Door>,
+    #[serde(default)]
+    pub locks: Vec<Lock>,
+    #[serde(default)]
+    pub hazards: Vec<Position>,
+    #[serde(default = "default_hazard_penalty")]
+    pub hazard_penalty: f64,
+    #[serde(default)]
+    pub adversaries: Vec<Adversary>,
+    #[serde(default)]
+    pub collision: Collision,
+    pub cells: Vec<CellDocument>,
+    #[serde(default)]
+    pub grid: Vec<String>,
+}
+
+fn one_level() -> usize {
+    1
+}
+
+fn default_hazard_penalty() -> f64 {
+    DEFAULT_HAZARD_PENALTY
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CellDocument {
+    pub x: usize,
+    pub y: usize,
+    pub wall: bool,
+    pub neighbors: Vec<Position>,
+}
+
+#[derive(Debug)]
+pub enum JsonError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    CellOutOfBounds(Position),
+    NotAdjacent(Position, Position),
+    GridSize { expected: (usize, usize), found: (usize, usize) },
+    BadLevels { width: usize, levels: usize },
+    BadWraparound { width: usize, height: usize },
+    /// A scheduled door that is not on a passage tile between two cells.
+    BadDoor(Position),
+    Placement(PlacementError),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::Io(err) => write!(f, "could not read maze file: {err}"),
+            JsonError::Parse(err) => write!(f, "invalid maze JSON: {err}"),
+            JsonError::CellOutOfBounds((x, y)) => write!(f, "cell ({x}, {y}) is outside the maze"),
+            JsonError::NotAdjacent(a, b) => write!(f, "cells {a:?} and {b:?} are linked but not adjacent"),
+            JsonError::GridSize { expected, found } => write!(
+                f,
+                "grid is {}x{}, expected {}x{} for the given dimensions",
+                found.0, found.1, expected.0, expected.1
+            ),
+            JsonError::BadLevels { width, levels } => {
+                write!(f, "cannot split a maze {width} cells wide into {levels} square floors")
+            }
+            JsonError::BadWraparound { width, height } => write!(
+                f,
+                "a {width}x{height} maze cannot wrap around; only single-level square mazes of at least 3x3 cells can"
+            ),
+            JsonError::BadDoor((x, y)) => write!(f, "door at ({x}, {y}) is not on a passage tile between two cells"),
+            JsonError::Placement(err) => write!(f, "invalid start, goal or waypoint: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JsonError::Io(err) => Some(err),
+            JsonError::Parse(err) => Some(err),
+            JsonError::Placement(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for JsonError {
+    fn from(err: std::io::Error) -> Self {
+        JsonError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(err: serde_json::Error) -> Self {
+        JsonError::Parse(err)
+    }
+}
+
+impl From<PlacementError> for JsonError {
+    fn from(err: PlacementError) -> Self {
+        JsonError::Placement(err)
+    }
+}
+
+impl Maze {
+    /// The character grid, one line per row, readable by [`Maze::from_ascii`] for square mazes.
+    /// Hex and triangle grids hold only the cells, so their walls are lost, and stairs between
+    /// floors and the grid of a wrapping maze need the JSON form.
+    pub fn to_ascii(&self) -> String {
+        let mut text = String::with_capacity(self.grid.height() * (self.grid.width() + 1));
+        for y in 0..self.grid.height() {
+            text.extend(self.grid.row(y));
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn save_ascii(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_ascii())
+    }
+
+    pub fn to_document(&self) -> MazeDocument {
+        let mut cells = Vec::with_capacity(self.dimension_x * self.dimension_y);
+        for y in 0..self.dimension_y {
+            for x in 0..self.dimension_x {
+                let neighbors = self.cell_neighbors((x, y)).collect();
+                cells.push(CellDocument { x, y, wall: self.cells.is_wall((x, y)), neighbors });
+            }
+        }
+
+        MazeDocument {
+            width: self.dimension_x,
+            height: self.dimension_y,
+            topology: self.topology,
+            levels: self.levels,
+            wrap: self.wrap,
+            seed: self.seed,
+            start: self.start_pos,
+            goal: self.end_pos,
+            waypoints: self.waypoints.clone(),
+            waypoint_order: self.waypoint_order,
+            doors: self.doors.clone(),
+            locks: self.locks.clone(),
+            hazards: self.hazards.clone(),
+            hazard_penalty: self.hazard_penalty,
+            adversaries: self.adversaries.clone(),
+            collision: self.collision,
+            cells,
+            grid: (0..self.grid.height()).map(|y| self.grid.row(y).collect()).collect(),
+        }
+    }
+
+    /// Rebuilds a maze, rejecting documents that do not fit together structurally. A grid that
+    /// disagrees with `cells` or a goal that cannot be reached is accepted; [`Maze::validate`]
+    /// reports those.
+    pub fn from_document(document: &MazeDocument) -> Result<Self, JsonError> {
+        let levels = document.levels;
+        if levels == 0 || !document.width.is_multiple_of(levels) || (levels > 1 && document.topology != Topology::Square) {
+            return Err(JsonError::BadLevels { width: document.width, levels });
+        }
+
+        let mut maze = Maze::blank(document.width, document.height, document.topology);
+        maze.levels = levels;
+        if document.wrap {
+            if document.width < 3 || document.height < 3 || levels > 1 || document.topology != Topology::Square {
+                return Err(JsonError::BadWraparound { width: document.width, height: document.height });
+            }
+            maze.make_wrapping();
+        }
+        let in_bounds = |(x, y): Position| x < document.width && y < document.height;
+
+        for cell in &document.cells {
+            if !in_bounds((cell.x, cell.y)) {
+                return Err(JsonError::CellOutOfBounds((cell.x, cell.y)));
+            }
+            maze.cells.set_wall((cell.x, cell.y), cell.wall);
+
+            for &neighbor in &cell.neighbors {
+                if !in_bounds(neighbor) {
+                    return Err(JsonError::CellOutOfBounds(neighbor));
+                }
+                // Only this side of the passage is set, so one-way links survive for validate.
+                let direction = maze
+                    .direction_to((cell.x, cell.y), neighbor)
+                    .ok_or(JsonError::NotAdjacent((cell.x, cell.y), neighbor))?;
+                maze.cells.set_link((cell.x, cell.y), direction, true);
+            }
+        }
+
+        if document.grid.is_empty() {
+            maze.update_grid();
+        } else {
+            let expected = (maze.grid.width(), maze.grid.height());
+            let grid: Vec<Vec<char>> = document.grid.iter().map(|row| row.chars().collect()).collect();
+            if let Some(row) = grid.iter().find(|row| row.len() != expected.0) {
+                return Err(JsonError::GridSize { expected, found: (row.len(), grid.len()) });
+            }
+            if grid.len() != expected.1 {
+                return Err(JsonError::GridSize { expected, found: (expected.0, grid.len()) });
+            }
+            maze.grid = TileGrid::from_rows(&grid);
+            for pos in [document.start, document.goal].into_iter().flatten() {
+                if maze.grid.get(pos).is_some() {
+                    maze.grid.set(pos, if maze.is_border_tile(pos) { '#' } else { ' ' });
+                }
+            }
+        }
+
+        if let (Some(start), Some(goal)) = (document.start, document.goal) {
+            maze.set_start_end(start, goal)?;
+        }
+        maze.set_waypoints(document.waypoints.clone(), document.waypoint_order)?;
+        if let Some(door) = document.doors.iter().find(|door| maze.door_cells(door.position).is_none()) {
+            return Err(JsonError::BadDoor(door.position));
+        }
+        maze.doors = document.doors.clone();
+        for lock in &document.locks {
+            maze.add_lock(lock.clone())?;
+        }
+        for &hazard in &document.hazards {
+            maze.add_hazard(hazard)?;
+        }
+        maze.hazard_penalty = document.hazard_penalty;
+        for adversary in &document.adversaries {
+            maze.add_adversary(adversary.clone())?;
+        }
+        maze.collision = document.collision;
+        maze.seed = document.seed;
+
+        Ok(maze)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_document()).expect("maze documents always serialize")
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, JsonError> {
+        Self::from_document(&serde_json::from_str(text)?)
+    }
+
+    pub fn save_json(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self, JsonError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Draws the grid as an SVG image, optionally overlaying a route given as grid positions.
+    ///
+    /// SVG is output only; use the ASCII or JSON forms to load a maze back.
+    pub fn to_svg(&self, solution: Option<&[Position]>) -> String {
+        let (canvas_x, canvas_y) = self.canvas_size();
+        let width = (canvas_x * SVG_TILE as f32).ceil() as usize;
+        let height = (canvas_y * SVG_TILE as f32).ceil() as usize;
+        let mut svg = String::new();
+
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        );
+        let _ = writeln!(svg, r#"<rect width="{width}" height="{height}" fill="white"/>"#);
+
+        for y in 0..self.grid.height() {
+            for (x, ch) in self.grid.row(y).enumerate() {
+                let fill = match ch {
+                    '#' => "dimgray",
+                    '*' => "green",
+                    '~' => "red",
+                    STAIRS_UP | STAIRS_DOWN | STAIRS_BOTH => "gold",
+                    _ => match Terrain::from_symbol(ch) {
+                        Some(Terrain::Road) => "tan",
+                        Some(Terrain::Mud) => "saddlebrown",
+                        Some(Terrain::Water) => "skyblue",
+                        _ => continue,
+                    },
+                };
+                self.write_svg_tile(&mut svg, (x, y), fill);
+            }
+        }
+
+        for &(a, b) in &self.wall_segments() {
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="dimgray" stroke-width="{}" stroke-linecap="round"/>"#,
+                svg_coord(a.0),
+                svg_coord(a.1),
+                svg_coord(b.0),
+                svg_coord(b.1),
+                SVG_TILE / 5
+            );
+        }
+
+        for &pos in &self.waypoints {
+            self.write_svg_tile(&mut svg, pos, "orange");
+        }
+
+        for lock in &self.locks {
+            self.write_svg_tile(&mut svg, lock.door, "maroon");
+            self.write_svg_tile(&mut svg, lock.key, "yellow");
+        }
+
+        for &pos in &self.hazards {
+            self.write_svg_tile(&mut svg, pos, "crimson");
+        }
+
+        if let Some(path) = solution.filter(|path| !path.is_empty()) {
+            let points: Vec<String> = path
+                .iter()
+                .map(|&pos| {
+                    let (x, y) = self.tile_center(pos);
+                    format!("{},{}", svg_coord(x), svg_coord(y))
+                })
+                .collect();
+            let _ = writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="blue" stroke-width="{}" stroke-linejoin="round" stroke-linecap="round"/>"#,
+                points.join(" "),
+                SVG_TILE / 3
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn write_svg_tile(&self, svg: &mut String, (x, y): Position, fill: &str) {
+        if self.topology == Topology::Square {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{SVG_TILE}" height="{SVG_TILE}" fill="{fill}"/>"#,
+                x * SVG_TILE,
+                y * SVG_TILE
+            );
+            return;
+        }
+
+        let points: Vec<String> = self
+            .tile_shape((x, y))
+            .iter()
+            .map(|&(x, y)| format!("{},{}", svg_coord(x), svg_coord(y)))
+            .collect();
+        let _ = writeln!(svg, r#"<polygon points="{}" fill="{fill}"/>"#, points.join(" "));
+    }
+
+    pub fn save_svg(&self, path: impl AsRef<Path>, solution: Option<&[Position]>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_svg(solution))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{Prims, RecursiveBacktracker, Wilsons};
+
+    fn assert_round_trips(maze: &Maze) {
+        let json = maze.to_json();
+        let restored = Maze::from_json(&json).unwrap();
+
+        assert_eq!(restored.topology, maze.topology);
+        assert_eq!((restored.levels, restored.wrap), (maze.levels, maze.wrap));
+        assert_eq!((restored.start_pos, restored.end_pos), (maze.start_pos, maze.end_pos));
+        assert_eq!(restored.cells, maze.cells);
+        assert_eq!(restored.grid, maze.grid);
+        assert_eq!(restored.to_json(), json);
+        assert!(restored.validate().is_empty());
+    }
+
+    #[test]
+    fn round_trips_square_mazes() {
+        assert_round_trips(&Maze::with_seed(9, 6, 1));
+    }
+
+    #[test]
+    fn round_trips_hex_mazes() {
+        assert_round_trips(&Maze::with_topology(7, 5, Topology::Hex, &Prims, 2));
+    }
+
+    #[test]
+    fn round_trips_triangle_mazes() {
+        assert_round_trips(&Maze::with_topology(8, 5, Topology::Triangle, &Wilsons, 3));
+    }
+
+    #[test]
+    fn round_trips_multi_level_mazes() {
+        assert_round_trips(&Maze::with_levels(4, 4, 3, &RecursiveBacktracker, 4));
+    }
+
+    #[test]
+    fn round_trips_wrapping_mazes() {
+        assert_round_trips(&Maze::with_wraparound(6, 5, &RecursiveBacktracker, 5));
+    }
+
+    #[test]
+    fn round_trips_terrain_and_placed_items() {
+        let mut maze = Maze::with_seed(8, 8, 6);
+        let route = maze.solve_bfs().unwrap().path;
+        let off_route = (1..maze.grid.height() - 1)
+            .flat_map(|y| (1..maze.grid.width() - 1).map(move |x| (x, y)))
+            .find(|&tile| maze.grid[tile] == ' ' && !route.contains(&tile))
+            .unwrap();
+
+        maze.set_terrain(route[2], Terrain::Mud).unwrap();
+        maze.set_terrain(route[3], Terrain::Water).unwrap();
+        maze.set_waypoints(vec![route[route.len() / 2]], WaypointOrder::Ordered).unwrap();
+        maze.add_hazard(off_route).unwrap();
+        assert_round_trips(&maze);
+    }
+
+    #[test]
+    fn rejects_doors_off_passage_tiles() {
+        for position in [(1, 1), (40, 3)] {
+            let mut document = Maze::with_seed(4, 4, 3).to_document();
+            document.doors.push(Door { position, period: 2, open_for: 1, phase: 0 });
+            assert!(matches!(Maze::from_document(&document), Err(JsonError::BadDoor(pos)) if pos == position));
+        }
+    }
+
+    #[test]
+    fn rejects_links_between_cells_that_do_not_touch() {
+        let mut document = Maze::with_seed(3, 3, 7).to_document();
+        document.cells[0].neighbors.push((2, 2));
+        assert!(matches!(Maze::from_document(&document), Err(JsonError::NotAdjacent((0, 0), (2, 2)))));
+    }
+}
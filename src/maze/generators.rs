@@ -0,0 +1,647 @@
+//generators.rs
+
+use super::{Maze, Topology};
+use ::rand::seq::SliceRandom;
+use ::rand::{Rng, RngCore};
+use std::collections::BTreeMap;
+
+/// A maze carving algorithm.
+///
+/// Generators receive a maze whose cells are all walls and open passages by linking
+/// neighbouring cells, which sets a passage bit on both cells. The maze turns that
+/// adjacency into its character grid afterwards.
+pub trait MazeGenerator {
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore);
+
+    /// Whether the generator can carve mazes of this cell shape.
+    ///
+    /// Generators that only follow neighbour lists work everywhere. Row-based generators
+    /// link `(x, y)` to `(x, y + 1)`, which hex cells always share but triangles only share
+    /// every other column.
+    fn supports(&self, _topology: Topology) -> bool {
+        true
+    }
+
+    /// Whether the generator carves only between cells the maze reports as adjacent, so it
+    /// follows stairs between floors and passages across the edges of a wrapping maze.
+    /// Row-based generators lay passages out by coordinates instead.
+    fn follows_neighbors(&self) -> bool {
+        true
+    }
+}
+
+/// Depth-first search with an explicit stack. Produces long, winding corridors with few branches.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RecursiveBacktracker;
+
+/// Randomised Prim's algorithm. Grows the maze outward from a frontier, giving many short dead ends.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Prims;
+
+/// Randomised Kruskal's algorithm. Joins disjoint regions in random order across the whole grid.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Kruskals;
+
+/// Kruskal's algorithm over fixed passage weights instead of a random order, for mazes that
+/// are evolved rather than drawn at random.
+///
+/// Pairs of adjacent cells take one weight each, column by column, and the lightest are
+/// carved first, ties in that order. Missing weights count as zero. The result is always a
+/// perfect maze joining every cell, and the same weights always give the same maze.
+#[derive(Clone, Debug, Default)]
+pub struct WeightedKruskals {
+    pub weights: Vec<u8>,
+}
+
+/// Wilson's algorithm. Loop-erased random walks give a uniformly random spanning tree.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Wilsons;
+
+/// Aldous-Broder algorithm. A plain random walk that also samples spanning trees uniformly, but slowly.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AldousBroder;
+
+/// Eller's algorithm. Builds the maze one row at a time while tracking connected sets.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ellers;
+
+/// Binary tree algorithm. Each cell opens north or east, leaving two unbroken corridors along those edges.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BinaryTree;
+
+/// Sidewinder algorithm. Carves eastward runs in each row and closes every run with one opening north.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sidewinder;
+
+/// Recursive division. Starts from an open field and adds walls with a single gap, giving long straight walls.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RecursiveDivision;
+
+/// Rooms and corridors. Places separate rectangular rooms, joins them with L-shaped corridors and
+/// leaves the rest as solid wall, so the maze has wide open areas instead of one-cell passages.
+///
+/// The westernmost and easternmost rooms get a corridor out to the left and right edges, where
+/// the start and goal are placed.
+#[derive(Clone, Copy, Debug)]
+pub struct Dungeon {
+    /// Rooms to place. Fewer may fit in a small maze, but there is always at least one.
+    pub rooms: usize,
+    /// Smallest and largest side of a room, in cells.
+    pub room_size: (usize, usize),
+    /// Corridors added between random rooms once all are joined, each usually closing a loop.
+    pub extra_corridors: usize,
+}
+
+impl Default for Dungeon {
+    fn default() -> Self {
+        Dungeon { rooms: 6, room_size: (2, 4), extra_corridors: 2 }
+    }
+}
+
+/// A room as `(x, y, width, height)` in cells.
+type Room = (usize, usize, usize, usize);
+
+impl MazeGenerator for RecursiveBacktracker {
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let mut stack = Vec::new();
+
+        maze.cells.set_wall((0, 0), false);
+        stack.push((0, 0));
+
+        while let Some((x, y)) = stack.pop() {
+            let neighbors = maze.adjacent((x, y)).filter(|&next| maze.cells.is_wall(next));
+            let Some((nx, ny)) = choose(neighbors, rng) else {
+                continue;
+            };
+
+            stack.push((x, y));
+
+            maze.link((x, y), (nx, ny));
+
+            stack.push((nx, ny));
+        }
+    }
+}
+
+impl MazeGenerator for Prims {
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        if maze.dimension_x == 0 || maze.dimension_y == 0 {
+            return;
+        }
+
+        // Cells already carved are exactly the ones no longer solid wall.
+        let height = maze.dimension_y;
+        let mut in_frontier = vec![false; maze.dimension_x * height];
+        let mut frontier = Vec::new();
+
+        let start = (rng.gen_range(0..maze.dimension_x), rng.gen_range(0..maze.dimension_y));
+        maze.cells.set_wall(start, false);
+
+        for next in maze.adjacent(start) {
+            in_frontier[next.0 * height + next.1] = true;
+            frontier.push(next);
+        }
+
+        while !frontier.is_empty() {
+            let (x, y) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+
+            let carved = maze.adjacent((x, y)).filter(|&next| !maze.cells.is_wall(next));
+            let target = choose(carved, rng).unwrap();
+
+            maze.link((x, y), target);
+
+            for (nx, ny) in maze.adjacent((x, y)) {
+                if maze.cells.is_wall((nx, ny)) && !in_frontier[nx * height + ny] {
+                    in_frontier[nx * height + ny] = true;
+                    frontier.push((nx, ny));
+                }
+            }
+        }
+    }
+}
+
+impl MazeGenerator for Kruskals {
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let mut edges = open_all_cells(maze);
+        edges.shuffle(rng);
+        join_regions(maze, edges);
+    }
+}
+
+impl WeightedKruskals {
+    /// Number of weights a maze of this size and cell shape reads, one per pair of adjacent cells.
+    pub fn weights_needed(dimension_x: usize, dimension_y: usize, topology: Topology) -> usize {
+        let maze = Maze::blank(dimension_x, dimension_y, topology);
+        (0..dimension_x)
+            .flat_map(|x| (0..dimension_y).map(move |y| (x, y)))
+            .map(|cell| maze.adjacent(cell).filter(|&next| next > cell).count())
+            .sum()
+    }
+}
+
+impl MazeGenerator for WeightedKruskals {
+    fn generate(&self, maze: &mut Maze, _rng: &mut dyn RngCore) {
+        let mut edges: Vec<_> = open_all_cells(maze)
+            .into_iter()
+            .enumerate()
+            .map(|(i, edge)| (self.weights.get(i).copied().unwrap_or(0), edge))
+            .collect();
+        edges.sort_by_key(|&(weight, _)| weight);
+        join_regions(maze, edges.into_iter().map(|(_, edge)| edge).collect());
+    }
+}
+
+/// Picks one of at most six neighbouring cells with the same draw [`SliceRandom::choose`]
+/// makes on a slice of them, so seeded mazes come out the same without allocating.
+fn choose(candidates: impl Iterator<Item = (usize, usize)>, rng: &mut dyn RngCore) -> Option<(usize, usize)> {
+    let mut found = [(0, 0); 6];
+    let mut count = 0;
+    for cell in candidates {
+        found[count] = cell;
+        count += 1;
+    }
+
+    (count > 0).then(|| found[rng.gen_range(0..count as u32) as usize])
+}
+
+/// Opens every cell and lists each pair of adjacent cells once, column by column, as the
+/// cells' indices in column order.
+fn open_all_cells(maze: &mut Maze) -> Vec<(usize, usize)> {
+    let height = maze.dimension_y;
+    let mut edges = Vec::with_capacity(maze.dimension_x * height * maze.directions() / 2);
+
+    for x in 0..maze.dimension_x {
+        for y in 0..height {
+            maze.cells.set_wall((x, y), false);
+            for next in maze.adjacent((x, y)) {
+                if next > (x, y) {
+                    edges.push((x * height + y, next.0 * height + next.1));
+                }
+            }
+        }
+    }
+
+    edges
+}
+
+/// Links each pair of cells in turn unless they are already joined, leaving a spanning tree.
+fn join_regions(maze: &mut Maze, edges: Vec<(usize, usize)>) {
+    let height = maze.dimension_y;
+    let mut parents: Vec<usize> = (0..maze.dimension_x * height).collect();
+    let mut sizes = vec![1; parents.len()];
+
+    fn find(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+
+    for (a, b) in edges {
+        let root_a = find(&mut parents, a);
+        let root_b = find(&mut parents, b);
+
+        if root_a != root_b {
+            // Hanging the smaller region under the larger keeps the trees shallow.
+            let (small, large) = if sizes[root_a] < sizes[root_b] { (root_a, root_b) } else { (root_b, root_a) };
+            parents[small] = large;
+            sizes[large] += sizes[small];
+            maze.link((a / height, a % height), (b / height, b % height));
+        }
+    }
+}
+
+impl MazeGenerator for Wilsons {
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        if maze.dimension_x == 0 || maze.dimension_y == 0 {
+            return;
+        }
+
+        let height = maze.dimension_y;
+        let index = |(x, y): (usize, usize)| x * height + y;
+
+        // Walks start from each cell in a random order, skipping cells an earlier walk reached.
+        let mut order: Vec<(usize, usize)> = (0..maze.dimension_x)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .collect();
+        order.shuffle(rng);
+
+        let mut in_maze = vec![false; order.len()];
+        in_maze[index(order[0])] = true;
+        maze.cells.set_wall(order[0], false);
+
+        // Where each cell sits on the current walk, or usize::MAX when it is not on it.
+        let mut index_of = vec![usize::MAX; order.len()];
+        let mut path = Vec::new();
+
+        for &start in &order[1..] {
+            if in_maze[index(start)] {
+                continue;
+            }
+
+            path.clear();
+            path.push(start);
+            index_of[index(start)] = 0;
+            let mut current = start;
+
+            while !in_maze[index(current)] {
+                let next = choose(maze.adjacent(current), rng).unwrap();
+
+                match index_of[index(next)] {
+                    usize::MAX => {
+                        index_of[index(next)] = path.len();
+                        path.push(next);
+                    }
+                    i => {
+                        for erased in path.drain(i + 1..) {
+                            index_of[index(erased)] = usize::MAX;
+                        }
+                    }
+                }
+
+                current = next;
+            }
+
+            for pair in path.windows(2) {
+                maze.link(pair[0], pair[1]);
+                in_maze[index(pair[0])] = true;
+            }
+            for &cell in &path {
+                index_of[index(cell)] = usize::MAX;
+            }
+        }
+    }
+}
+
+impl MazeGenerator for AldousBroder {
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        if maze.dimension_x == 0 || maze.dimension_y == 0 {
+            return;
+        }
+
+        let mut visited = vec![vec![false; maze.dimension_y]; maze.dimension_x];
+        let mut unvisited = maze.dimension_x * maze.dimension_y - 1;
+
+        let mut current = (rng.gen_range(0..maze.dimension_x), rng.gen_range(0..maze.dimension_y));
+        visited[current.0][current.1] = true;
+        maze.cells.set_wall(current, false);
+
+        while unvisited > 0 {
+            let next = choose(maze.adjacent(current), rng).unwrap();
+
+            if !visited[next.0][next.1] {
+                maze.link(current, next);
+                visited[next.0][next.1] = true;
+                unvisited -= 1;
+            }
+
+            current = next;
+        }
+    }
+}
+
+impl MazeGenerator for Ellers {
+    fn supports(&self, topology: Topology) -> bool {
+        topology != Topology::Triangle
+    }
+
+    fn follows_neighbors(&self) -> bool {
+        false
+    }
+
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let width = maze.dimension_x;
+        let height = maze.dimension_y;
+        let mut sets = vec![0usize; width];
+        let mut next_set = 1;
+
+        for y in 0..height {
+            let last_row = y + 1 == height;
+
+            for set in sets.iter_mut().filter(|set| **set == 0) {
+                *set = next_set;
+                next_set += 1;
+            }
+
+            for x in 0..width.saturating_sub(1) {
+                if sets[x] != sets[x + 1] && (last_row || rng.gen_bool(0.5)) {
+                    maze.link((x, y), (x + 1, y));
+
+                    let (merged, kept) = (sets[x + 1], sets[x]);
+                    for set in sets.iter_mut().filter(|set| **set == merged) {
+                        *set = kept;
+                    }
+                }
+            }
+
+            if last_row {
+                if width == 1 {
+                    maze.cells.set_wall((0, y), false);
+                }
+                break;
+            }
+
+            let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+            for (x, &set) in sets.iter().enumerate() {
+                members.entry(set).or_default().push(x);
+            }
+
+            let mut next_row = vec![0usize; width];
+            for (set, columns) in members {
+                let mut drops: Vec<usize> = columns.iter().copied().filter(|_| rng.gen_bool(0.5)).collect();
+                if drops.is_empty() {
+                    drops.push(*columns.choose(rng).unwrap());
+                }
+
+                for x in drops {
+                    maze.link((x, y), (x, y + 1));
+                    next_row[x] = set;
+                }
+            }
+
+            sets = next_row;
+        }
+    }
+}
+
+impl MazeGenerator for BinaryTree {
+    fn supports(&self, topology: Topology) -> bool {
+        topology != Topology::Triangle
+    }
+
+    fn follows_neighbors(&self) -> bool {
+        false
+    }
+
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        for y in 0..maze.dimension_y {
+            for x in 0..maze.dimension_x {
+                let mut candidates = Vec::with_capacity(2);
+                if y > 0 {
+                    candidates.push((x, y - 1));
+                }
+                if x + 1 < maze.dimension_x {
+                    candidates.push((x + 1, y));
+                }
+
+                match candidates.choose(rng) {
+                    Some(&next) => maze.link((x, y), next),
+                    None => maze.cells.set_wall((x, y), false),
+                }
+            }
+        }
+    }
+}
+
+impl MazeGenerator for Sidewinder {
+    fn supports(&self, topology: Topology) -> bool {
+        topology != Topology::Triangle
+    }
+
+    fn follows_neighbors(&self) -> bool {
+        false
+    }
+
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        for y in 0..maze.dimension_y {
+            let mut run = Vec::new();
+
+            for x in 0..maze.dimension_x {
+                run.push(x);
+
+                let at_east = x + 1 == maze.dimension_x;
+                let at_north = y == 0;
+
+                if at_east || (!at_north && rng.gen_bool(0.5)) {
+                    if !at_north {
+                        let &member = run.choose(rng).unwrap();
+                        maze.link((member, y), (member, y - 1));
+                    } else {
+                        maze.cells.set_wall((x, y), false);
+                    }
+                    run.clear();
+                } else {
+                    maze.link((x, y), (x + 1, y));
+                }
+            }
+        }
+    }
+}
+
+impl MazeGenerator for RecursiveDivision {
+    fn supports(&self, topology: Topology) -> bool {
+        topology != Topology::Triangle
+    }
+
+    fn follows_neighbors(&self) -> bool {
+        false
+    }
+
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        for x in 0..maze.dimension_x {
+            for y in 0..maze.dimension_y {
+                maze.cells.set_wall((x, y), false);
+                if x + 1 < maze.dimension_x {
+                    maze.link((x, y), (x + 1, y));
+                }
+                if y + 1 < maze.dimension_y {
+                    maze.link((x, y), (x, y + 1));
+                }
+            }
+        }
+
+        let mut stack = vec![(0, 0, maze.dimension_x, maze.dimension_y)];
+
+        while let Some((x, y, width, height)) = stack.pop() {
+            if width < 2 || height < 2 {
+                continue;
+            }
+
+            let horizontal = match width.cmp(&height) {
+                std::cmp::Ordering::Less => true,
+                std::cmp::Ordering::Greater => false,
+                std::cmp::Ordering::Equal => rng.gen_bool(0.5),
+            };
+
+            if horizontal {
+                let wall_y = y + rng.gen_range(0..height - 1);
+                let gap_x = x + rng.gen_range(0..width);
+
+                for cx in (x..x + width).filter(|&cx| cx != gap_x) {
+                    maze.unlink((cx, wall_y), (cx, wall_y + 1));
+                }
+
+                stack.push((x, y, width, wall_y - y + 1));
+                stack.push((x, wall_y + 1, width, y + height - wall_y - 1));
+            } else {
+                let wall_x = x + rng.gen_range(0..width - 1);
+                let gap_y = y + rng.gen_range(0..height);
+
+                for cy in (y..y + height).filter(|&cy| cy != gap_y) {
+                    maze.unlink((wall_x, cy), (wall_x + 1, cy));
+                }
+
+                stack.push((x, y, wall_x - x + 1, height));
+                stack.push((wall_x + 1, y, x + width - wall_x - 1, height));
+            }
+        }
+    }
+}
+
+impl MazeGenerator for Dungeon {
+    fn supports(&self, topology: Topology) -> bool {
+        topology != Topology::Triangle
+    }
+
+    fn follows_neighbors(&self) -> bool {
+        false
+    }
+
+    fn generate(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let (width, height) = (maze.dimension_x, maze.dimension_y);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let min_side = self.room_size.0.max(1);
+        let max_side = self.room_size.1.max(min_side);
+        let wanted = self.rooms.max(1);
+        let mut rooms: Vec<Room> = Vec::new();
+
+        for _ in 0..wanted * 10 {
+            if rooms.len() == wanted {
+                break;
+            }
+
+            let room_width = rng.gen_range(min_side..=max_side).min(width);
+            let room_height = rng.gen_range(min_side..=max_side).min(height);
+            let x = rng.gen_range(0..=width - room_width);
+            let y = rng.gen_range(0..=height - room_height);
+
+            // Rooms keep a cell apart so corridors, not shared walls, join them.
+            let crowded = rooms
+                .iter()
+                .any(|&(rx, ry, rw, rh)| x <= rx + rw && rx <= x + room_width && y <= ry + rh && ry <= y + room_height);
+            if !crowded {
+                rooms.push((x, y, room_width, room_height));
+            }
+        }
+
+        for &(x, y, room_width, room_height) in &rooms {
+            for cx in x..x + room_width {
+                for cy in y..y + room_height {
+                    maze.cells.set_wall((cx, cy), false);
+                    if cx + 1 < x + room_width {
+                        maze.link((cx, cy), (cx + 1, cy));
+                    }
+                    if cy + 1 < y + room_height {
+                        maze.link((cx, cy), (cx, cy + 1));
+                    }
+                }
+            }
+        }
+
+        let center = |&(x, y, room_width, room_height): &Room| (x + room_width / 2, y + room_height / 2);
+
+        for i in 1..rooms.len() {
+            let j = rng.gen_range(0..i);
+            dig_corridor(maze, center(&rooms[i]), center(&rooms[j]), rng);
+        }
+
+        if rooms.len() > 1 {
+            for _ in 0..self.extra_corridors {
+                let a = rng.gen_range(0..rooms.len());
+                let b = (a + rng.gen_range(1..rooms.len())) % rooms.len();
+                dig_corridor(maze, center(&rooms[a]), center(&rooms[b]), rng);
+            }
+        }
+
+        let &(west_x, west_y, _, west_height) = rooms.iter().min_by_key(|room| room.0).unwrap();
+        let row = rng.gen_range(west_y..west_y + west_height);
+        dig_corridor(maze, (west_x, row), (0, row), rng);
+
+        let &(east_x, east_y, east_width, east_height) = rooms.iter().max_by_key(|room| room.0 + room.2).unwrap();
+        let row = rng.gen_range(east_y..east_y + east_height);
+        dig_corridor(maze, (east_x + east_width - 1, row), (width - 1, row), rng);
+    }
+}
+
+/// Links cells along an L-shaped path from `from` to `to`, turning the corner either way.
+fn dig_corridor(maze: &mut Maze, from: (usize, usize), to: (usize, usize), rng: &mut dyn RngCore) {
+    let toward = |from: usize, to: usize| match from.cmp(&to) {
+        std::cmp::Ordering::Less => from + 1,
+        std::cmp::Ordering::Greater => from - 1,
+        std::cmp::Ordering::Equal => from,
+    };
+    let corner = if rng.gen_bool(0.5) { (to.0, from.1) } else { (from.0, to.1) };
+    let mut current = from;
+    maze.cells.set_wall(from, false);
+
+    for target in [corner, to] {
+        while current != target {
+            let next = (toward(current.0, target.0), toward(current.1, target.1));
+            maze.link(current, next);
+            current = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::SeedableRng;
+    use ::rand::rngs::StdRng;
+
+    #[test]
+    fn choose_draws_like_slice_choose() {
+        let cells = [(0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11)];
+        let mut slice_rng = StdRng::seed_from_u64(11);
+        let mut iter_rng = StdRng::seed_from_u64(11);
+
+        for len in (0..=cells.len()).cycle().take(60) {
+            let expected = cells[..len].choose(&mut slice_rng).copied();
+            assert_eq!(choose(cells[..len].iter().copied(), &mut iter_rng), expected);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//graph.rs
+
+use super::{Maze, Position};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A corridor between two nodes of a [`JunctionGraph`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JunctionEdge {
+    pub from: Position,
+    pub to: Position,
+    /// Cell-to-cell steps along the corridor.
+    pub length: usize,
+    /// Cells along the corridor from `from` to `to`, both included.
+    pub path: Vec<Position>,
+}
+
+/// The cell graph with every corridor of two-passage cells collapsed into one weighted edge.
+///
+/// Nodes are dead ends, junctions, and the cells the start and goal sit on or open into.
+/// A loop with none of those on it keeps one of its cells as a node. Positions are cell
+/// coordinates; see [`Maze::cell_to_tile`] for the grid positions routes move through.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JunctionGraph {
+    pub nodes: Vec<Position>,
+    pub edges: Vec<JunctionEdge>,
+}
+
+impl JunctionGraph {
+    /// Edges touching `node`, each paired with the node at its other end.
+    pub fn edges_from(&self, node: Position) -> impl Iterator<Item = (Position, &JunctionEdge)> + '_ {
+        self.edges.iter().filter_map(move |edge| {
+            if edge.from == node {
+                Some((edge.to, edge))
+            } else if edge.to == node {
+                Some((edge.from, edge))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl Maze {
+    /// Cells that are not solid wall, column by column.
+    pub fn open_cells(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.dimension_x)
+            .flat_map(move |x| (0..self.dimension_y).map(move |y| (x, y)))
+            .filter(|&cell| !self.cells.is_wall(cell))
+    }
+
+    /// Cells joined to `cell` by a passage, including stairs and passages across the edges of
+    /// a wrapping maze. Empty for a cell outside the maze.
+    pub fn cell_neighbors(&self, cell: Position) -> impl Iterator<Item = Position> + '_ {
+        let links = if self.contains_cell(cell) { self.cells.links(cell) } else { 0 };
+        (0..8)
+            .filter(move |direction| links & (1 << direction) != 0)
+            .filter_map(move |direction| self.cell_in_direction(cell, direction))
+    }
+
+    /// Whether a passage joins cells `a` and `b`.
+    pub fn is_linked(&self, a: Position, b: Position) -> bool {
+        self.contains_cell(a)
+            && self.direction_to(a, b).is_some_and(|direction| self.cells.links(a) & (1 << direction) != 0)
+    }
+
+    /// Number of passages out of `cell`.
+    pub fn degree(&self, cell: Position) -> usize {
+        if self.contains_cell(cell) { self.cells.links(cell).count_ones() as usize } else { 0 }
+    }
+
+    /// Adjacency lists for every open cell.
+    pub fn adjacency(&self) -> BTreeMap<Position, Vec<Position>> {
+        self.open_cells().map(|cell| (cell, self.cell_neighbors(cell).collect())).collect()
+    }
+
+    /// Open cells with exactly one passage.
+    pub fn dead_ends(&self) -> impl Iterator<Item = Position> + '_ {
+        self.open_cells().filter(|&cell| self.degree(cell) == 1)
+    }
+
+    /// Open cells with three or more passages.
+    pub fn junctions(&self) -> impl Iterator<Item = Position> + '_ {
+        self.open_cells().filter(|&cell| self.degree(cell) >= 3)
+    }
+
+    fn contains_cell(&self, (x, y): Position) -> bool {
+        x < self.dimension_x && y < self.dimension_y
+    }
+
+    pub fn junction_graph(&self) -> JunctionGraph {
+        let mut nodes: BTreeSet<Position> = self.open_cells().filter(|&cell| self.degree(cell) != 2).collect();
+        nodes.extend(self.start_pos.into_iter().chain(self.end_pos).map(|pos| self.nearest_cell(pos)));
+
+        let mut edges = Vec::new();
+        let mut walked: BTreeSet<(Position, Position)> = BTreeSet::new();
+        let mut covered: BTreeSet<Position> = nodes.clone();
+        let mut pending: Vec<Position> = nodes.iter().copied().collect();
+
+        loop {
+            for &node in &pending {
+                for first in self.cell_neighbors(node) {
+                    if !walked.insert((node, first)) {
+                        continue;
+                    }
+
+                    let mut path = vec![node, first];
+                    while !nodes.contains(&path[path.len() - 1]) {
+                        let (previous, current) = (path[path.len() - 2], path[path.len() - 1]);
+                        covered.insert(current);
+                        let next = self.cell_neighbors(current).find(|&n| n != previous).unwrap_or(previous);
+                        path.push(next);
+                    }
+
+                    let (last, before) = (path[path.len() - 1], path[path.len() - 2]);
+                    walked.insert((last, before));
+                    edges.push(JunctionEdge { from: node, to: last, length: path.len() - 1, path });
+                }
+            }
+
+            // Whatever is left sits on loops without a node; promote one cell per loop.
+            let Some(cell) = self.open_cells().find(|cell| !covered.contains(cell)) else {
+                break;
+            };
+            nodes.insert(cell);
+            covered.insert(cell);
+            pending = vec![cell];
+        }
+
+        JunctionGraph { nodes: nodes.into_iter().collect(), edges }
+    }
+}
@@ -0,0 +1,115 @@
+//hazards.rs
+
+use super::{Maze, PlacementError, Position};
+use ::rand::seq::SliceRandom;
+use ::rand::RngCore;
+
+/// Fitness lost by a route that steps on a hazard, unless changed with [`Maze::set_hazard_penalty`].
+pub const DEFAULT_HAZARD_PENALTY: f64 = 100.0;
+
+impl Maze {
+    /// Turns an open tile inside the outer wall into a hazard. A route that steps on it stops
+    /// there and loses [`Maze::hazard_penalty`], unlike a bump into a wall, which only skips
+    /// the move.
+    ///
+    /// Hazards may not share a tile with the start, the goal, a waypoint, a key or a door, and
+    /// are rejected with [`PlacementError::Blocked`] if every way to the goal would run over one
+    /// while the scheduled doors are closed.
+    pub fn add_hazard(&mut self, (x, y): Position) -> Result<(), PlacementError> {
+        if y >= self.grid.height() || x >= self.grid.width() {
+            return Err(PlacementError::OutOfBounds((x, y)));
+        }
+        if self.is_border_tile((x, y)) || self.grid[(x, y)] == '#' {
+            return Err(PlacementError::Blocked((x, y)));
+        }
+        let taken = Some((x, y)) == self.start_pos
+            || Some((x, y)) == self.end_pos
+            || self.waypoints.contains(&(x, y))
+            || self.hazards.contains(&(x, y))
+            || self.doors.iter().any(|door| door.position == (x, y))
+            || self.locks.iter().any(|lock| lock.key == (x, y) || lock.door == (x, y));
+        if taken {
+            return Err(PlacementError::Occupied((x, y)));
+        }
+
+        self.hazards.push((x, y));
+        if !self.solvable_with_doors_closed() {
+            self.hazards.pop();
+            return Err(PlacementError::Blocked((x, y)));
+        }
+
+        Ok(())
+    }
+
+    /// Tries up to every open tile in random order and adds at most `count` hazards.
+    /// Returns the number of hazards added.
+    pub fn add_random_hazards(&mut self, count: usize, rng: &mut dyn RngCore) -> usize {
+        let mut candidates: Vec<Position> = self.open_tiles().collect();
+        candidates.shuffle(rng);
+
+        let mut added = 0;
+        for pos in candidates {
+            if added == count {
+                break;
+            }
+            if self.add_hazard(pos).is_ok() {
+                added += 1;
+            }
+        }
+
+        added
+    }
+
+    pub fn hazards(&self) -> &[Position] {
+        &self.hazards
+    }
+
+    pub fn is_hazard(&self, pos: Position) -> bool {
+        self.hazards.contains(&pos)
+    }
+
+    pub fn hazard_penalty(&self) -> f64 {
+        self.hazard_penalty
+    }
+
+    pub fn set_hazard_penalty(&mut self, penalty: f64) {
+        self.hazard_penalty = penalty;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::SeedableRng;
+    use ::rand::rngs::StdRng;
+
+    #[test]
+    fn hazards_leave_a_safe_route_at_every_generation_of_the_door_schedule() {
+        for seed in 0..40 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut maze = Maze::with_seed(15, 15, seed);
+            maze.braid(0.5, &mut rng);
+            maze.add_random_doors(4, 40, 20, &mut rng);
+            maze.update_doors(0);
+            maze.add_random_hazards(10, &mut rng);
+
+            for generation in 0..40 {
+                maze.update_doors(generation);
+                assert!(maze.solve_bfs().is_some(), "seed {seed} has no safe route at generation {generation}");
+            }
+        }
+    }
+
+    #[test]
+    fn start_and_goal_cannot_move_onto_a_hazard() {
+        let mut maze = Maze::with_seed(6, 6, 2);
+        let (start, end) = (maze.start_pos().unwrap(), maze.end_pos().unwrap());
+        let route = maze.solve_bfs().unwrap().path;
+        let hazard = maze.open_tiles().find(|tile| !route.contains(tile)).unwrap();
+        maze.add_hazard(hazard).unwrap();
+
+        assert_eq!(maze.set_start_end(hazard, end), Err(PlacementError::Occupied(hazard)));
+        assert_eq!(maze.set_start_end(start, hazard), Err(PlacementError::Occupied(hazard)));
+        assert_eq!((maze.start_pos(), maze.end_pos()), (Some(start), Some(end)));
+    }
+}
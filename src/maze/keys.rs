@@ -0,0 +1,147 @@
+//keys.rs
+
+use super::{Maze, PlacementError, Position};
+use ::rand::seq::SliceRandom;
+use ::rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// A locked door and the key that opens it.
+///
+/// The door tile stays an open tile in the grid and the cell graph; routes simply cannot
+/// step onto it until they have stepped on the key. Keys are kept once picked up.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lock {
+    pub key: Position,
+    pub door: Position,
+}
+
+impl Maze {
+    /// Adds a locked door and its key, both on open tiles inside the outer wall.
+    ///
+    /// Keys and doors may not share a tile with each other, the start, the goal, a hazard or a
+    /// scheduled door. The lock is rejected with [`PlacementError::Blocked`] if the goal could
+    /// no longer be reached with every waypoint while the scheduled doors are closed, for
+    /// example because the key sits behind its own door.
+    pub fn add_lock(&mut self, lock: Lock) -> Result<(), PlacementError> {
+        for (x, y) in [lock.key, lock.door] {
+            if y >= self.grid.height() || x >= self.grid.width() {
+                return Err(PlacementError::OutOfBounds((x, y)));
+            }
+            if self.is_border_tile((x, y)) || self.grid[(x, y)] == '#' {
+                return Err(PlacementError::Blocked((x, y)));
+            }
+            let taken = Some((x, y)) == self.start_pos
+                || Some((x, y)) == self.end_pos
+                || self.hazards.contains(&(x, y))
+                || self.doors.iter().any(|door| door.position == (x, y))
+                || self.locks.iter().any(|other| other.key == (x, y) || other.door == (x, y));
+            if taken {
+                return Err(PlacementError::Occupied((x, y)));
+            }
+        }
+        if lock.key == lock.door {
+            return Err(PlacementError::Occupied(lock.key));
+        }
+
+        let door = lock.door;
+        self.locks.push(lock);
+        if !self.solvable_with_doors_closed() {
+            self.locks.pop();
+            return Err(PlacementError::Blocked(door));
+        }
+
+        Ok(())
+    }
+
+    /// Tries to add `count` locks whose doors sit on the current shortest route, so each one
+    /// forces a detour to its key. Returns the number of locks added.
+    pub fn add_random_locks(&mut self, count: usize, rng: &mut dyn RngCore) -> usize {
+        let mut added = 0;
+
+        for _ in 0..count * 20 {
+            if added == count {
+                break;
+            }
+            let Some(solution) = self.solve_bfs() else {
+                break;
+            };
+
+            let on_route: Vec<Position> = solution.path.iter().copied().filter(|&pos| !self.is_border_tile(pos)).collect();
+            let open: Vec<Position> = self.open_tiles().collect();
+            let (Some(&door), Some(&key)) = (on_route.choose(rng), open.choose(rng)) else {
+                break;
+            };
+
+            if self.add_lock(Lock { key, door }).is_ok() {
+                added += 1;
+            }
+        }
+
+        added
+    }
+
+    pub fn locks(&self) -> &[Lock] {
+        &self.locks
+    }
+
+    /// For each lock, the index into `path` at which its key was picked up, if it was.
+    pub fn key_pickups(&self, path: &[Position]) -> Vec<Option<usize>> {
+        self.locks.iter().map(|lock| path.iter().position(|&pos| pos == lock.key)).collect()
+    }
+
+    /// Whether `pos` is a door whose key is not among `keys`, one flag per lock.
+    pub(super) fn is_locked(&self, pos: Position, keys: &[bool]) -> bool {
+        self.locks.iter().zip(keys).any(|(lock, &held)| lock.door == pos && !held)
+    }
+
+    /// Picks up the key on `pos`, if any. Returns whether a new key was picked up.
+    pub(super) fn pick_up_key(&self, pos: Position, keys: &mut [bool]) -> bool {
+        match self.locks.iter().position(|lock| lock.key == pos) {
+            Some(i) if !keys[i] => {
+                keys[i] = true;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::SeedableRng;
+    use ::rand::rngs::StdRng;
+
+    #[test]
+    fn locks_leave_a_route_at_every_generation_of_the_door_schedule() {
+        for seed in 0..10 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut maze = Maze::with_seed(15, 15, seed);
+            maze.braid(0.5, &mut rng);
+            maze.add_random_doors(4, 40, 20, &mut rng);
+            maze.update_doors(0);
+            maze.add_random_locks(3, &mut rng);
+
+            for generation in 0..40 {
+                maze.update_doors(generation);
+                assert!(maze.solve_bfs().is_some(), "seed {seed} has no route at generation {generation}");
+            }
+        }
+    }
+
+    #[test]
+    fn doors_leave_a_route_past_existing_locks() {
+        for seed in 0..10 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut maze = Maze::with_seed(15, 15, seed);
+            maze.braid(0.5, &mut rng);
+            maze.add_random_locks(3, &mut rng);
+            maze.add_random_doors(4, 40, 20, &mut rng);
+
+            for generation in 0..40 {
+                maze.update_doors(generation);
+                assert!(maze.solve_bfs().is_some(), "seed {seed} has no route at generation {generation}");
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//levels.rs
+
+use super::{Maze, Position, Topology};
+
+/// Grid character for a cell with stairs up to the next floor.
+pub const STAIRS_UP: char = '>';
+/// Grid character for a cell with stairs down to the previous floor.
+pub const STAIRS_DOWN: char = '<';
+/// Grid character for a cell with stairs both ways.
+pub const STAIRS_BOTH: char = '%';
+
+impl Maze {
+    /// Number of stacked floors. Flat mazes have one.
+    ///
+    /// Floors are laid out side by side, ground floor on the left: floor `z` holds cells
+    /// `z * level_width()..(z + 1) * level_width()` and neighbouring floors share a wall column
+    /// in the grid. Stairs link a cell to the cell at the same spot one floor up or down.
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// Width of one floor in cells.
+    pub fn level_width(&self) -> usize {
+        self.dimension_x / self.levels
+    }
+
+    /// The floor a grid position is on.
+    pub fn level_of(&self, (x, _): Position) -> usize {
+        (x.saturating_sub(1) / (self.level_width() * 2)).min(self.levels - 1)
+    }
+
+    /// Number of distinct moves a route can make: the topology's moves, plus up and down on
+    /// multi-level mazes.
+    pub fn directions(&self) -> usize {
+        self.topology.directions() + if self.levels > 1 { 2 } else { 0 }
+    }
+
+    /// Bits needed per move in a binary genome, for use as the GA's `gene_length`.
+    pub fn gene_length(&self) -> usize {
+        super::topology::bits_for(self.directions())
+    }
+
+    pub(super) fn cell_level(&self, (x, _): Position) -> usize {
+        x / self.level_width()
+    }
+
+    /// Grid character for a cell's stairs, if it has any.
+    pub(super) fn stair_symbol(&self, cell: Position) -> Option<char> {
+        let links = self.cells.links(cell);
+        let up = links & (1 << 4) != 0;
+        let down = links & (1 << 5) != 0;
+
+        match (up, down) {
+            (true, true) => Some(STAIRS_BOTH),
+            (true, false) => Some(STAIRS_UP),
+            (false, true) => Some(STAIRS_DOWN),
+            (false, false) => None,
+        }
+    }
+
+    /// Takes the stairs up or down from a cell tile, or `None` if the cell has no such stairs.
+    pub(super) fn stair_step(&self, (x, y): Position, up: bool) -> Option<Position> {
+        if self.levels == 1 || self.topology != Topology::Square {
+            return None;
+        }
+        let cell = self.tile_to_cell((x, y))?;
+        let direction = if up { 4 } else { 5 };
+        if self.cells.links(cell) & (1 << direction) == 0 {
+            return None;
+        }
+
+        self.cell_in_direction(cell, direction).map(|target| self.cell_to_tile(target))
+    }
+}
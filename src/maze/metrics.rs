@@ -0,0 +1,241 @@
+//metrics.rs
+
+use super::{Maze, Position};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Distinct solutions are counted up to this many; braided mazes can have far more.
+pub const SOLUTION_COUNT_LIMIT: usize = 1000;
+
+/// Cells the distinct-solution search may step into before it gives up. Without it a braided
+/// maze can take minutes to explore even when few of its branches reach the goal.
+pub const SOLUTION_SEARCH_LIMIT: usize = 200_000;
+
+/// Structure and difficulty numbers for a maze, computed from its cell graph.
+///
+/// Lengths are in cell-to-cell steps. Start and goal count as the cells they sit on or
+/// open into, and waypoints are ignored.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MazeMetrics {
+    pub open_cells: usize,
+    /// Cells with exactly one passage.
+    pub dead_ends: usize,
+    /// Cells with three or more passages.
+    pub junctions: usize,
+    /// Steps along the shortest route from start to goal, if the goal can be reached.
+    pub solution_length: Option<usize>,
+    /// The longest shortest path between any two cells.
+    pub longest_path: usize,
+    /// Average number of onward passages from a cell entered through one of its passages,
+    /// over every cell that is not a dead end.
+    pub branching_factor: f64,
+    /// Solution length divided by the Manhattan distance between start and goal.
+    pub tortuosity: Option<f64>,
+    /// Routes from start to goal that never revisit a cell, counted up to [`SOLUTION_COUNT_LIMIT`].
+    pub distinct_solutions: usize,
+    /// Whether the count stopped at [`SOLUTION_COUNT_LIMIT`] or [`SOLUTION_SEARCH_LIMIT`] before
+    /// every route was tried, so there may be more.
+    pub distinct_solutions_capped: bool,
+    /// Share of steps along the solution that do not bring the Manhattan distance to the goal
+    /// down. 0 means the distance heuristic is never misleading.
+    pub manhattan_deception: f64,
+}
+
+impl fmt::Display for MazeMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "unreachable".to_string());
+        let solutions = if self.distinct_solutions_capped {
+            format!("{}+", self.distinct_solutions)
+        } else {
+            self.distinct_solutions.to_string()
+        };
+
+        writeln!(f, "{:<20} {}", "Open cells", self.open_cells)?;
+        writeln!(f, "{:<20} {}", "Dead ends", self.dead_ends)?;
+        writeln!(f, "{:<20} {}", "Junctions", self.junctions)?;
+        writeln!(f, "{:<20} {}", "Solution length", optional(self.solution_length.map(|n| n.to_string())))?;
+        writeln!(f, "{:<20} {}", "Longest path", self.longest_path)?;
+        writeln!(f, "{:<20} {:.2}", "Branching factor", self.branching_factor)?;
+        writeln!(f, "{:<20} {}", "Tortuosity", optional(self.tortuosity.map(|t| format!("{t:.2}"))))?;
+        writeln!(f, "{:<20} {}", "Distinct solutions", solutions)?;
+        write!(f, "{:<20} {:.1}%", "Manhattan deception", self.manhattan_deception * 100.0)
+    }
+}
+
+impl Maze {
+    pub fn metrics(&self) -> MazeMetrics {
+        let open: Vec<Position> = self.open_cells().collect();
+        let dead_ends = self.dead_ends().count();
+        let junctions = self.junctions().count();
+        let passable: Vec<usize> = open.iter().map(|&cell| self.degree(cell)).filter(|&d| d >= 2).collect();
+        let branching_factor = if passable.is_empty() {
+            0.0
+        } else {
+            passable.iter().map(|&d| (d - 1) as f64).sum::<f64>() / passable.len() as f64
+        };
+
+        let longest_path = open
+            .iter()
+            .map(|&cell| self.cell_distances(cell).into_iter().flatten().flatten().max().unwrap_or(0))
+            .max()
+            .unwrap_or(0);
+
+        let ends = self.start_pos.zip(self.end_pos).map(|(start, end)| (self.nearest_cell(start), self.nearest_cell(end)));
+        let solution = ends.and_then(|(start, goal)| self.cell_path(start, goal));
+        let solution_length = solution.as_ref().map(|path| path.len() - 1);
+
+        let (tortuosity, manhattan_deception, (distinct_solutions, distinct_solutions_capped)) = match (ends, &solution) {
+            (Some((start, goal)), Some(path)) => {
+                let direct = self.cell_distance(start, goal);
+                let tortuosity = (direct > 0).then(|| (path.len() - 1) as f64 / direct as f64);
+
+                let misleading = path
+                    .windows(2)
+                    .filter(|step| self.cell_distance(step[1], goal) >= self.cell_distance(step[0], goal))
+                    .count();
+                let deception = if path.len() > 1 { misleading as f64 / (path.len() - 1) as f64 } else { 0.0 };
+
+                (tortuosity, deception, self.count_solutions(start, goal))
+            }
+            _ => (None, 0.0, (0, false)),
+        };
+
+        MazeMetrics {
+            open_cells: open.len(),
+            dead_ends,
+            junctions,
+            solution_length,
+            longest_path,
+            branching_factor,
+            tortuosity,
+            distinct_solutions,
+            distinct_solutions_capped,
+            manhattan_deception,
+        }
+    }
+
+    /// The cell a grid position sits on, or opens into for border and passage tiles.
+    pub(super) fn nearest_cell(&self, (x, y): Position) -> Position {
+        match self.tile_to_cell((x, y)) {
+            Some(cell) => cell,
+            None => (
+                (x.saturating_sub(1) / 2).min(self.dimension_x - 1),
+                (y.saturating_sub(1) / 2).min(self.dimension_y - 1),
+            ),
+        }
+    }
+
+    /// Manhattan distance between cells, counting each floor change as one step and taking
+    /// the short way around a wrapping maze.
+    fn cell_distance(&self, a: Position, b: Position) -> usize {
+        let width = self.level_width();
+        let floors = (a.0 / width).abs_diff(b.0 / width);
+        let dx = (a.0 % width).abs_diff(b.0 % width);
+        let dy = a.1.abs_diff(b.1);
+
+        if self.wrap {
+            dx.min(width - dx) + dy.min(self.dimension_y - dy)
+        } else {
+            dx + dy + floors
+        }
+    }
+
+    /// Breadth-first step counts from `from` along the cell graph, indexed `[x][y]`.
+    fn cell_distances(&self, from: Position) -> Vec<Vec<Option<usize>>> {
+        let mut distances = vec![vec![None; self.dimension_y]; self.dimension_x];
+        let mut queue = VecDeque::from([from]);
+        distances[from.0][from.1] = Some(0);
+
+        while let Some((x, y)) = queue.pop_front() {
+            let next_distance = distances[x][y].map(|d| d + 1);
+            for (nx, ny) in self.cell_neighbors((x, y)) {
+                if distances[nx][ny].is_none() {
+                    distances[nx][ny] = next_distance;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// A shortest cell path from `from` to `to`, both included.
+    fn cell_path(&self, from: Position, to: Position) -> Option<Vec<Position>> {
+        let distances = self.cell_distances(to);
+        let mut current = from;
+        let mut path = vec![current];
+
+        while current != to {
+            let here = distances[current.0][current.1]?;
+            current = self.cell_neighbors(current).find(|&(nx, ny)| distances[nx][ny] == Some(here - 1))?;
+            path.push(current);
+        }
+
+        Some(path)
+    }
+
+    /// Simple paths from `from` to `to`, and whether the search gave up early at
+    /// [`SOLUTION_COUNT_LIMIT`] solutions or [`SOLUTION_SEARCH_LIMIT`] cells stepped into.
+    fn count_solutions(&self, from: Position, to: Position) -> (usize, bool) {
+        let mut on_path = vec![vec![false; self.dimension_y]; self.dimension_x];
+        let mut stack = vec![(from, 0)];
+        let mut count = 0;
+        let mut explored = 0;
+        on_path[from.0][from.1] = true;
+
+        while let Some(top) = stack.last_mut() {
+            let ((x, y), next) = *top;
+            if count >= SOLUTION_COUNT_LIMIT || explored >= SOLUTION_SEARCH_LIMIT {
+                return (count, true);
+            }
+            if (x, y) == to {
+                count += 1;
+                on_path[x][y] = false;
+                stack.pop();
+                continue;
+            }
+
+            match self.cell_neighbors((x, y)).nth(next) {
+                Some((nx, ny)) => {
+                    top.1 += 1;
+                    if !on_path[nx][ny] {
+                        on_path[nx][ny] = true;
+                        explored += 1;
+                        stack.push(((nx, ny), 0));
+                    }
+                }
+                None => {
+                    on_path[x][y] = false;
+                    stack.pop();
+                }
+            }
+        }
+
+        (count, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{Prims, RecursiveBacktracker};
+    use ::rand::SeedableRng;
+    use ::rand::rngs::StdRng;
+
+    #[test]
+    fn perfect_mazes_have_one_solution() {
+        let metrics = Maze::with_generator(15, 15, &RecursiveBacktracker, 1).metrics();
+        assert_eq!(metrics.distinct_solutions, 1);
+        assert!(!metrics.distinct_solutions_capped);
+    }
+
+    #[test]
+    fn solution_search_gives_up_on_heavily_braided_mazes() {
+        let mut maze = Maze::with_generator(25, 25, &Prims, 1);
+        maze.braid(1.0, &mut StdRng::seed_from_u64(1));
+        let metrics = maze.metrics();
+        assert!(metrics.distinct_solutions_capped);
+        assert!(metrics.to_string().contains(&format!("{}+", metrics.distinct_solutions)));
+    }
+}
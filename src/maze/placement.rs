@@ -0,0 +1,285 @@
+//placement.rs
+
+use super::{Maze, Position, Topology};
+use ::rand::seq::SliceRandom;
+use ::rand::{Rng, RngCore};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// How [`Maze::place_start_end_with`] chooses the start and goal tiles.
+///
+/// All positions are grid coordinates `(x, y)`, the same space as [`Maze::start_pos`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Placement {
+    /// Start on the right edge in the first open row, goal on the left edge in the last open row.
+    /// Wrapping mazes have no edges, so there the goal is the cell halfway round from the first.
+    OppositeSides,
+    /// Fixed start and goal tiles, validated like [`Maze::set_start_end`].
+    Explicit { start: (usize, usize), end: (usize, usize) },
+    /// Two distinct openings in the outer wall, on any edge.
+    Edge,
+    /// Two distinct cells away from the outer ring of cells.
+    Interior,
+    /// Two cells in diagonally opposite corners of the maze.
+    OppositeCorners,
+    /// Two distinct cells anywhere in the maze.
+    Random,
+    /// The two ends of the longest shortest path between cells.
+    LongestPath,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlacementError {
+    OutOfBounds((usize, usize)),
+    Blocked((usize, usize)),
+    SamePosition((usize, usize)),
+    /// The tile already holds the start, goal or another placed item.
+    Occupied((usize, usize)),
+    NoOpenTiles,
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlacementError::OutOfBounds((x, y)) => write!(f, "position ({x}, {y}) is outside the grid"),
+            PlacementError::Blocked((x, y)) => write!(f, "position ({x}, {y}) is not an open tile or a border opening"),
+            PlacementError::SamePosition((x, y)) => write!(f, "start and goal are both at ({x}, {y})"),
+            PlacementError::Occupied((x, y)) => write!(f, "position ({x}, {y}) is already taken by another item"),
+            PlacementError::NoOpenTiles => write!(f, "the maze has no open tiles to place the start and goal on"),
+        }
+    }
+}
+
+impl std::error::Error for PlacementError {}
+
+impl Maze {
+    /// Moves the start and goal to the given grid positions.
+    ///
+    /// Each position must be an open tile or a tile in the outer wall that borders an open tile.
+    /// Tiles with terrain, stairs, a door, a lock, a waypoint or a hazard are rejected with
+    /// [`PlacementError::Occupied`], since the start and goal markers would overwrite them.
+    /// On error the maze keeps its previous start and goal.
+    pub fn set_start_end(&mut self, start: (usize, usize), end: (usize, usize)) -> Result<(), PlacementError> {
+        self.check_placeable(start)?;
+        self.check_placeable(end)?;
+        if start == end {
+            return Err(PlacementError::SamePosition(start));
+        }
+        if let Some(&taken) = [start, end].iter().find(|&&pos| self.is_occupied(pos)) {
+            return Err(PlacementError::Occupied(taken));
+        }
+
+        self.clear_start_end();
+        self.start_pos = Some(start);
+        self.end_pos = Some(end);
+        self.mark_start_end();
+        Ok(())
+    }
+
+    /// Replaces the start and goal using the given strategy.
+    pub fn place_start_end_with(&mut self, placement: &Placement, rng: &mut dyn RngCore) -> Result<(), PlacementError> {
+        let open_cells: Vec<Position> = self.open_cell_tiles().into_iter().filter(|&tile| !self.is_occupied(tile)).collect();
+
+        let (start, end) = match placement {
+            Placement::OppositeSides => {
+                self.clear_start_end();
+                self.start_pos = None;
+                self.end_pos = None;
+                self.place_start_end();
+                return match (self.start_pos, self.end_pos) {
+                    (Some(_), Some(_)) => Ok(()),
+                    _ => Err(PlacementError::NoOpenTiles),
+                };
+            }
+            Placement::Explicit { start, end } => (*start, *end),
+            Placement::Edge => {
+                let openings: Vec<Position> = self.border_openings().into_iter().filter(|&tile| !self.is_occupied(tile)).collect();
+                Self::pick_two(&openings, rng)?
+            }
+            Placement::Interior => {
+                let inner: Vec<(usize, usize)> = open_cells
+                    .iter()
+                    .copied()
+                    .filter(|&tile| {
+                        self.tile_to_cell(tile).is_some_and(|(x, y)| {
+                            x > 0 && y > 0 && x + 1 < self.dimension_x && y + 1 < self.dimension_y
+                        })
+                    })
+                    .collect();
+                if inner.len() >= 2 {
+                    Self::pick_two(&inner, rng)?
+                } else {
+                    Self::pick_two(&open_cells, rng)?
+                }
+            }
+            Placement::OppositeCorners => {
+                let (far_x, far_y) = (self.dimension_x.saturating_sub(1), self.dimension_y.saturating_sub(1));
+                let diagonals = [
+                    (self.cell_to_tile((0, 0)), self.cell_to_tile((far_x, far_y))),
+                    (self.cell_to_tile((far_x, 0)), self.cell_to_tile((0, far_y))),
+                ];
+                let &(a, b) = diagonals.choose(rng).unwrap();
+                if rng.gen_bool(0.5) { (a, b) } else { (b, a) }
+            }
+            Placement::Random => Self::pick_two(&open_cells, rng)?,
+            Placement::LongestPath => {
+                let &first = open_cells.first().ok_or(PlacementError::NoOpenTiles)?;
+                let a = self.farthest_tile(first);
+                let b = self.farthest_tile(a);
+                (a, b)
+            }
+        };
+
+        self.set_start_end(start, end)
+    }
+
+    pub(super) fn check_placeable(&self, (x, y): (usize, usize)) -> Result<(), PlacementError> {
+        if y >= self.grid.height() || x >= self.grid.width() {
+            return Err(PlacementError::OutOfBounds((x, y)));
+        }
+
+        let placeable = if self.is_border_tile((x, y)) {
+            self.border_openings().contains(&(x, y))
+        } else {
+            self.grid[(x, y)] != '#'
+        };
+
+        if placeable { Ok(()) } else { Err(PlacementError::Blocked((x, y))) }
+    }
+
+    /// Whether `pos` holds something the start or goal marker would overwrite. The current
+    /// start and goal tiles are free to reuse.
+    fn is_occupied(&self, pos: Position) -> bool {
+        let marked = Some(pos) == self.start_pos || Some(pos) == self.end_pos;
+        (!marked && self.grid.get(pos).is_some_and(|ch| ch != ' ' && ch != '#'))
+            || self.doors.iter().any(|door| door.position == pos)
+            || self.locks.iter().any(|lock| lock.key == pos || lock.door == pos)
+            || self.waypoints.contains(&pos)
+            || self.hazards.contains(&pos)
+    }
+
+    /// Whether `pos` is in the outer wall. Only square mazes that do not wrap have one.
+    pub(super) fn is_border_tile(&self, (x, y): (usize, usize)) -> bool {
+        self.topology == Topology::Square && !self.wrap && (x == 0 || y == 0 || x + 1 == self.grid.width() || y + 1 == self.grid.height())
+    }
+
+    /// Non-corner tiles of the outer wall that sit next to an open cell. Mazes without an
+    /// outer wall use the open cells on the outer ring instead.
+    fn border_openings(&self) -> Vec<(usize, usize)> {
+        if self.topology != Topology::Square || self.wrap {
+            return self
+                .open_cell_tiles()
+                .into_iter()
+                .filter(|&tile| {
+                    self.tile_to_cell(tile).is_some_and(|(x, y)| {
+                        x == 0 || y == 0 || x + 1 == self.dimension_x || y + 1 == self.dimension_y
+                    })
+                })
+                .collect();
+        }
+
+        let width = self.grid.width();
+        let height = self.grid.height();
+        let mut openings = Vec::new();
+
+        for &(x, y) in &self.open_cell_tiles() {
+            if x == 1 {
+                openings.push((0, y));
+            }
+            if x + 2 == width {
+                openings.push((width - 1, y));
+            }
+            if y == 1 {
+                openings.push((x, 0));
+            }
+            if y + 2 == height {
+                openings.push((x, height - 1));
+            }
+        }
+
+        openings
+    }
+
+    pub(super) fn open_cell_tiles(&self) -> Vec<(usize, usize)> {
+        let mut tiles = Vec::new();
+        for y in 0..self.dimension_y {
+            for x in 0..self.dimension_x {
+                if !self.cells.is_wall((x, y)) {
+                    tiles.push(self.cell_to_tile((x, y)));
+                }
+            }
+        }
+        tiles
+    }
+
+    /// Breadth-first search over the open grid tiles, returning the last tile reached.
+    fn farthest_tile(&self, from: (usize, usize)) -> (usize, usize) {
+        let mut seen = vec![vec![false; self.grid.width()]; self.grid.height()];
+        let mut queue = VecDeque::from([from]);
+        seen[from.1][from.0] = true;
+        let mut last = from;
+
+        while let Some(pos) = queue.pop_front() {
+            last = pos;
+            for (nx, ny) in self.open_neighbors(pos) {
+                if !seen[ny][nx] && !self.is_border_tile((nx, ny)) {
+                    seen[ny][nx] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        last
+    }
+
+    fn pick_two(tiles: &[(usize, usize)], rng: &mut dyn RngCore) -> Result<(Position, Position), PlacementError> {
+        if tiles.len() < 2 {
+            return Err(PlacementError::NoOpenTiles);
+        }
+        let picked: Vec<&(usize, usize)> = tiles.choose_multiple(rng, 2).collect();
+        Ok((*picked[0], *picked[1]))
+    }
+
+    fn clear_start_end(&mut self) {
+        for pos in [self.start_pos, self.end_pos].into_iter().flatten() {
+            self.grid.set(pos, if self.is_border_tile(pos) { '#' } else { ' ' });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{Terrain, WaypointOrder};
+    use ::rand::SeedableRng;
+    use ::rand::rngs::StdRng;
+
+    #[test]
+    fn start_and_goal_cannot_move_onto_terrain_or_placed_items() {
+        let mut maze = Maze::with_seed(6, 6, 3);
+        let (start, end) = (maze.start_pos.unwrap(), maze.end_pos.unwrap());
+        let mut free = maze.open_tiles();
+        let (mud, waypoint) = (free.next().unwrap(), free.next().unwrap());
+        drop(free);
+        maze.set_terrain(mud, Terrain::Mud).unwrap();
+        maze.set_waypoints(vec![waypoint], WaypointOrder::Ordered).unwrap();
+
+        assert_eq!(maze.set_start_end(mud, end), Err(PlacementError::Occupied(mud)));
+        assert_eq!(maze.set_start_end(start, waypoint), Err(PlacementError::Occupied(waypoint)));
+        assert_eq!(maze.terrain_at(mud), Some(Terrain::Mud));
+        assert_eq!((maze.start_pos, maze.end_pos), (Some(start), Some(end)));
+    }
+
+    #[test]
+    fn random_placement_skips_terrain() {
+        let mut maze = Maze::with_seed(6, 6, 3);
+        let mut rng = StdRng::seed_from_u64(3);
+        maze.add_terrain_patches(Terrain::Water, 4, 2, &mut rng);
+        let painted: Vec<Position> = maze.open_tiles().filter(|&tile| maze.terrain_at(tile) == Some(Terrain::Water)).collect();
+
+        for _ in 0..20 {
+            maze.place_start_end_with(&Placement::Random, &mut rng).unwrap();
+            assert!(painted.iter().all(|&tile| maze.terrain_at(tile) == Some(Terrain::Water)));
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//sensors.rs
+
+use super::{Maze, Position};
+use ::rand::{Rng, RngCore};
+use std::collections::HashSet;
+
+/// How far and how reliably an agent senses its surroundings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SensorConfig {
+    /// Furthest distance, in moves, a wall is sensed at. A radius of 0 counts as 1.
+    pub radius: usize,
+    /// Chance of each reading being wrong: a wall distance off by one, or a goal hint
+    /// pointing a random way.
+    pub noise: f64,
+}
+
+impl Default for SensorConfig {
+    fn default() -> Self {
+        SensorConfig { radius: 3, noise: 0.0 }
+    }
+}
+
+/// What an agent senses on its tile, with one entry per move direction as numbered by
+/// [`Maze::step`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    /// Whether a move that way is blocked by a wall or a door the agent has no key for.
+    pub walls: Vec<bool>,
+    /// Moves the agent could make that way in a straight line, up to the sensor radius.
+    pub wall_distances: Vec<usize>,
+    /// The smallest of `wall_distances`.
+    pub nearest_wall: usize,
+    /// The move that takes the agent closest to its next waypoint or the goal, or `None` if no
+    /// open move gets any closer.
+    pub goal_hint: Option<u8>,
+    /// Whether the agent has stood on this tile before.
+    pub visited: bool,
+}
+
+/// An agent walking the maze one move at a time, as a route does in [`Maze::test_route`].
+///
+/// Agents collect waypoints and keys, cannot pass locked doors without the key, and stop on a
+/// hazard or once the goal is reached with every waypoint. Adversaries are not simulated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Agent {
+    pub position: Position,
+    pub visited: HashSet<Position>,
+    /// Whether the agent has stopped, on a hazard or at the goal.
+    pub finished: bool,
+    collected: Vec<bool>,
+    keys: Vec<bool>,
+    revisiting: bool,
+}
+
+impl Maze {
+    /// An agent on the start tile, or `None` if the maze has no start.
+    pub fn agent(&self) -> Option<Agent> {
+        let start = self.start_pos?;
+        Some(Agent {
+            position: start,
+            visited: HashSet::from([start]),
+            finished: false,
+            collected: vec![false; self.waypoints.len()],
+            keys: vec![false; self.locks.len()],
+            revisiting: false,
+        })
+    }
+
+    /// Senses the surroundings of `agent`, drawing sensor noise from `rng`.
+    pub fn observe(&self, agent: &Agent, sensors: &SensorConfig, rng: &mut dyn RngCore) -> Observation {
+        let radius = sensors.radius.max(1);
+        let directions = self.directions() as u8;
+
+        let wall_distances: Vec<usize> = (0..directions)
+            .map(|direction| {
+                let mut distance = 0;
+                let mut pos = agent.position;
+                while distance < radius
+                    && let Some(next) = self.agent_step(agent, pos, direction)
+                {
+                    distance += 1;
+                    pos = next;
+                }
+
+                if rng.r#gen::<f64>() < sensors.noise {
+                    if distance == 0 || (distance < radius && rng.r#gen::<bool>()) { distance + 1 } else { distance - 1 }
+                } else {
+                    distance
+                }
+            })
+            .collect();
+
+        let target = self.next_target(agent.position, &agent.collected);
+        let mut goal_hint = target.and_then(|target| {
+            let here = self.grid_distance(agent.position, target);
+            (0..directions)
+                .filter_map(|direction| Some((direction, self.agent_step(agent, agent.position, direction)?)))
+                .map(|(direction, next)| (direction, self.grid_distance(next, target)))
+                .filter(|&(_, distance)| distance < here)
+                .min_by_key(|&(_, distance)| distance)
+                .map(|(direction, _)| direction)
+        });
+        if rng.r#gen::<f64>() < sensors.noise {
+            goal_hint = Some(rng.gen_range(0..directions));
+        }
+
+        Observation {
+            walls: wall_distances.iter().map(|&distance| distance == 0).collect(),
+            nearest_wall: wall_distances.iter().copied().min().unwrap_or(0),
+            wall_distances,
+            goal_hint,
+            visited: agent.revisiting,
+        }
+    }
+
+    /// Moves `agent` one step, picking up whatever is on the new tile. Returns whether it moved;
+    /// a finished agent or a blocked move leaves it where it is.
+    pub fn move_agent(&self, agent: &mut Agent, direction: u8) -> bool {
+        if agent.finished {
+            return false;
+        }
+        let Some(next) = self.agent_step(agent, agent.position, direction) else {
+            return false;
+        };
+
+        agent.position = next;
+        agent.revisiting = !agent.visited.insert(next);
+        if let Some(i) = self.collectable_waypoint(next, &agent.collected) {
+            agent.collected[i] = true;
+        }
+        self.pick_up_key(next, &mut agent.keys);
+        agent.finished = self.is_hazard(next) || (Some(next) == self.end_pos && agent.collected.iter().all(|&done| done));
+        true
+    }
+
+    /// Lets `policy` steer an agent for up to `steps` moves, observing before each one, and
+    /// returns the moves it chose. They can be scored with [`Maze::test_route`] or drawn with
+    /// [`Maze::trace_route`] like any fixed route.
+    pub fn run_policy(
+        &self,
+        steps: usize,
+        sensors: &SensorConfig,
+        rng: &mut dyn RngCore,
+        mut policy: impl FnMut(&Observation) -> u8,
+    ) -> Vec<u8> {
+        let Some(mut agent) = self.agent() else {
+            return Vec::new();
+        };
+
+        let mut moves = Vec::with_capacity(steps);
+        while moves.len() < steps && !agent.finished {
+            let direction = policy(&self.observe(&agent, sensors, rng));
+            self.move_agent(&mut agent, direction);
+            moves.push(direction);
+        }
+
+        moves
+    }
+
+    fn agent_step(&self, agent: &Agent, pos: Position, direction: u8) -> Option<Position> {
+        self.step(pos, direction).filter(|&next| !self.is_locked(next, &agent.keys))
+    }
+}
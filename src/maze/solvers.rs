@@ -0,0 +1,306 @@
+//solvers.rs
+
+use super::{Maze, Position, Terrain, Topology, WaypointOrder};
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+/// An optimal route from the start to the goal, collecting every waypoint on the way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution {
+    /// Grid positions from the start to the goal, both included.
+    pub path: Vec<Position>,
+    /// Number of moves, `path.len() - 1`.
+    pub steps: usize,
+    /// Total terrain cost of the tiles entered, as charged by [`Maze::test_route`].
+    pub cost: f64,
+}
+
+/// Search state: a position and what has been collected so far, one bit per waypoint followed
+/// by one bit per key.
+type State = (Position, u64);
+
+#[derive(PartialEq)]
+struct Frontier {
+    priority: f64,
+    cost: f64,
+    state: State,
+}
+
+impl Eq for Frontier {}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.total_cmp(&self.priority).then_with(|| other.state.cmp(&self.state))
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Maze {
+    /// Breadth-first search for the route with the fewest moves, ignoring terrain cost.
+    ///
+    /// Like the other solvers, this follows the same rules as [`Maze::test_route`], locked doors
+    /// and hazards included but adversaries left out, and supports up to 64 waypoints and keys
+    /// together. Returns `None` if the goal cannot be reached.
+    pub fn solve_bfs(&self) -> Option<Solution> {
+        let start = (self.start_pos?, self.collect(self.start_pos?, 0));
+        let mut parents: HashMap<State, State> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        parents.insert(start, start);
+
+        while let Some(state) = queue.pop_front() {
+            if self.is_solved(state) {
+                return Some(self.solution(&parents, state));
+            }
+            for next in self.next_states(state) {
+                if let Entry::Vacant(entry) = parents.entry(next) {
+                    entry.insert(state);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Dijkstra's algorithm for the route with the lowest terrain cost.
+    pub fn solve_dijkstra(&self) -> Option<Solution> {
+        self.best_first(false)
+    }
+
+    /// A* search for the route with the lowest terrain cost, guided by the distance to the
+    /// next waypoint or the goal. Finds the same cost as [`Maze::solve_dijkstra`] while
+    /// usually expanding fewer tiles.
+    pub fn solve_astar(&self) -> Option<Solution> {
+        self.best_first(true)
+    }
+
+    /// How close a route comes to the cheapest one: the optimal cost divided by the cost of
+    /// the route as traced by [`Maze::trace_route`]. 1.0 is optimal. Returns `None` if the
+    /// route does not reach the goal with every waypoint collected.
+    pub fn route_optimality(&self, route: &[u8]) -> Option<f64> {
+        let path = self.trace_route(route);
+        let reached = path.last().copied() == self.end_pos && self.waypoint_pickups(&path).iter().all(Option::is_some);
+        if !reached {
+            return None;
+        }
+
+        let cost = self.path_cost(&path);
+        let optimal = self.solve_dijkstra()?.cost;
+        Some(if cost > 0.0 { optimal / cost } else { 1.0 })
+    }
+
+    fn best_first(&self, guided: bool) -> Option<Solution> {
+        let start = (self.start_pos?, self.collect(self.start_pos?, 0));
+        let mut best: HashMap<State, f64> = HashMap::from([(start, 0.0)]);
+        let mut parents: HashMap<State, State> = HashMap::from([(start, start)]);
+        let mut heap = BinaryHeap::from([Frontier { priority: 0.0, cost: 0.0, state: start }]);
+
+        while let Some(Frontier { cost, state, .. }) = heap.pop() {
+            if cost > best[&state] {
+                continue;
+            }
+            if self.is_solved(state) {
+                return Some(self.solution(&parents, state));
+            }
+
+            for next in self.next_states(state) {
+                let next_cost = cost + self.step_cost(next.0).unwrap_or(1.0);
+                if best.get(&next).is_some_and(|&known| known <= next_cost) {
+                    continue;
+                }
+
+                best.insert(next, next_cost);
+                parents.insert(next, state);
+                let estimate = if guided { self.cost_estimate(next) } else { 0.0 };
+                heap.push(Frontier { priority: next_cost + estimate, cost: next_cost, state: next });
+            }
+        }
+
+        None
+    }
+
+    fn next_states(&self, (pos, collected): State) -> Vec<State> {
+        let keys = self.held_keys(collected);
+        (0..self.directions() as u8)
+            .filter_map(|direction| self.step(pos, direction))
+            .filter(|&next| next != pos && !self.is_locked(next, &keys) && !self.is_hazard(next))
+            .map(|next| (next, self.collect(next, collected)))
+            .collect()
+    }
+
+    /// Adds the waypoint and key picked up by stepping on `pos` to `collected`.
+    fn collect(&self, pos: Position, collected: u64) -> u64 {
+        let waypoints = self.waypoints.len();
+        assert!(waypoints + self.locks.len() <= 64, "solvers support at most 64 waypoints and keys together");
+
+        let flags: Vec<bool> = (0..waypoints).map(|i| collected & (1 << i) != 0).collect();
+        let mut collected = match self.collectable_waypoint(pos, &flags) {
+            Some(i) => collected | (1 << i),
+            None => collected,
+        };
+        if let Some(i) = self.locks.iter().position(|lock| lock.key == pos) {
+            collected |= 1 << (waypoints + i);
+        }
+        collected
+    }
+
+    fn held_keys(&self, collected: u64) -> Vec<bool> {
+        let waypoints = self.waypoints.len();
+        (0..self.locks.len()).map(|i| collected & (1 << (waypoints + i)) != 0).collect()
+    }
+
+    fn is_solved(&self, (pos, collected): State) -> bool {
+        Some(pos) == self.end_pos && (0..self.waypoints.len()).all(|i| collected & (1 << i) != 0)
+    }
+
+    fn solution(&self, parents: &HashMap<State, State>, goal: State) -> Solution {
+        let mut path = vec![goal.0];
+        let mut state = goal;
+        while parents[&state] != state {
+            state = parents[&state];
+            path.push(state.0);
+        }
+        path.reverse();
+
+        Solution { steps: path.len() - 1, cost: self.path_cost(&path), path }
+    }
+
+    /// Cost of walking `path`. Staying on a tile after a blocked move costs nothing.
+    fn path_cost(&self, path: &[Position]) -> f64 {
+        path.windows(2)
+            .filter(|pair| pair[0] != pair[1])
+            .map(|pair| self.step_cost(pair[1]).unwrap_or(1.0))
+            .sum()
+    }
+
+    /// A lower bound on the cost still to pay: the fewest moves to the next target, each at
+    /// the cheapest terrain cost.
+    fn cost_estimate(&self, (pos, collected): State) -> f64 {
+        let mut remaining = (0..self.waypoints.len())
+            .filter(|&i| collected & (1 << i) == 0)
+            .map(|i| self.waypoints[i])
+            .peekable();
+        let targets: Vec<Position> = match (self.waypoint_order, remaining.peek()) {
+            (_, None) => self.end_pos.into_iter().collect(),
+            (WaypointOrder::Ordered, Some(&next)) => vec![next],
+            (WaypointOrder::Unordered, Some(_)) => remaining.collect(),
+        };
+
+        let cheapest = Terrain::ALL.iter().map(|terrain| terrain.cost()).fold(f64::INFINITY, f64::min);
+        let moves = targets.iter().map(|&target| self.move_distance(pos, target)).min().unwrap_or(0);
+        moves as f64 * cheapest
+    }
+
+    /// The fewest moves between two grid positions if there were no walls.
+    fn move_distance(&self, a: Position, b: Position) -> usize {
+        match self.topology {
+            Topology::Hex => {
+                let axial = |(x, y): Position| (x as isize - (y as isize - (y as isize & 1)) / 2, y as isize);
+                let ((q1, r1), (q2, r2)) = (axial(a), axial(b));
+                let (dq, dr) = (q1 - q2, r1 - r2);
+                ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as usize
+            }
+            _ if self.levels > 1 => {
+                let floor_width = self.level_width() * 2;
+                let (ax, bx) = (a.0.saturating_sub(1) % floor_width, b.0.saturating_sub(1) % floor_width);
+                ax.abs_diff(bx) + a.1.abs_diff(b.1) + self.level_of(a).abs_diff(self.level_of(b))
+            }
+            _ => self.grid_distance(a, b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{Prims, RecursiveBacktracker};
+    use ::rand::SeedableRng;
+    use ::rand::rngs::StdRng;
+
+    /// A braided maze, so there are several routes to choose between, with patches of
+    /// costly terrain.
+    fn rough_maze(topology: Topology, seed: u64) -> Maze {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut maze = Maze::from_topology(10, 8, topology, &Prims, &mut rng);
+        maze.braid(0.8, &mut rng);
+        maze.add_terrain_patches(Terrain::Mud, 3, 2, &mut rng);
+        maze.add_terrain_patches(Terrain::Water, 2, 1, &mut rng);
+        maze.add_terrain_patches(Terrain::Road, 3, 2, &mut rng);
+        maze
+    }
+
+    fn assert_walkable(maze: &Maze, solution: &Solution) {
+        assert_eq!(solution.path.first().copied(), maze.start_pos);
+        assert_eq!(solution.path.last().copied(), maze.end_pos);
+        assert_eq!(solution.steps, solution.path.len() - 1);
+        for pair in solution.path.windows(2) {
+            assert!((0..maze.directions() as u8).any(|direction| maze.step(pair[0], direction) == Some(pair[1])));
+        }
+    }
+
+    fn assert_same_cost(maze: &Maze) {
+        let dijkstra = maze.solve_dijkstra().unwrap();
+        let astar = maze.solve_astar().unwrap();
+        let bfs = maze.solve_bfs().unwrap();
+
+        assert_walkable(maze, &dijkstra);
+        assert_walkable(maze, &astar);
+        assert_walkable(maze, &bfs);
+        assert!((astar.cost - dijkstra.cost).abs() < 1e-9, "A* cost {} but Dijkstra cost {}", astar.cost, dijkstra.cost);
+        assert!((maze.path_cost(&astar.path) - astar.cost).abs() < 1e-9);
+        assert!(bfs.steps <= dijkstra.steps);
+        assert!(bfs.cost >= dijkstra.cost - 1e-9);
+    }
+
+    #[test]
+    fn astar_and_dijkstra_find_equal_costs() {
+        for topology in [Topology::Square, Topology::Hex, Topology::Triangle] {
+            for seed in 0..8 {
+                assert_same_cost(&rough_maze(topology, seed));
+            }
+        }
+    }
+
+    #[test]
+    fn astar_and_dijkstra_find_equal_costs_through_waypoints() {
+        for order in [WaypointOrder::Ordered, WaypointOrder::Unordered] {
+            for seed in 0..8 {
+                let mut maze = rough_maze(Topology::Square, seed);
+                let route = maze.solve_bfs().unwrap().path;
+                let waypoints = maze
+                    .open_cell_tiles()
+                    .into_iter()
+                    .filter(|tile| !route.contains(tile))
+                    .step_by(7)
+                    .take(3)
+                    .collect();
+                maze.set_waypoints(waypoints, order).unwrap();
+                assert_same_cost(&maze);
+            }
+        }
+    }
+
+    #[test]
+    fn astar_and_dijkstra_find_equal_costs_across_levels() {
+        for seed in 0..4 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut maze = Maze::with_levels(6, 5, 2, &RecursiveBacktracker, seed);
+            maze.add_terrain_patches(Terrain::Mud, 3, 2, &mut rng);
+            assert_same_cost(&maze);
+        }
+    }
+
+    #[test]
+    fn solvers_agree_when_the_goal_is_cut_off() {
+        let maze = Maze::from_ascii("#####\n*   #\n#####\n~   #\n#####\n").unwrap();
+        assert!(maze.solve_bfs().is_none());
+        assert!(maze.solve_dijkstra().is_none());
+        assert!(maze.solve_astar().is_none());
+    }
+}
@@ -0,0 +1,152 @@
+//storage.rs
+
+use super::Position;
+use std::collections::HashMap;
+use std::ops::Index;
+
+/// A fixed number of bits packed into words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    fn new(len: usize, value: bool) -> Self {
+        let fill = if value { u64::MAX } else { 0 };
+        BitSet { words: vec![fill; len.div_ceil(64)] }
+    }
+
+    fn get(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    fn set(&mut self, i: usize, value: bool) {
+        if value {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+}
+
+/// The character grid of a [`Maze`](super::Maze), indexed by `(x, y)` grid position.
+///
+/// Walls take one bit per tile. Open tiles are `' '` unless they hold a marker, stairs or
+/// terrain, which are kept in a side table, so a large maze costs little more than its walls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileGrid {
+    width: usize,
+    height: usize,
+    walls: BitSet,
+    marks: HashMap<usize, char>,
+}
+
+impl TileGrid {
+    /// A grid of solid wall.
+    pub(super) fn new(width: usize, height: usize) -> Self {
+        TileGrid { width, height, walls: BitSet::new(width * height, true), marks: HashMap::new() }
+    }
+
+    /// A grid holding `rows`, which must all be the same length.
+    pub(super) fn from_rows(rows: &[Vec<char>]) -> Self {
+        let mut grid = TileGrid::new(rows.first().map_or(0, Vec::len), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &ch) in row.iter().enumerate() {
+                grid.set((x, y), ch);
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The character at `pos`, or `None` outside the grid.
+    pub fn get(&self, pos: Position) -> Option<char> {
+        (pos.0 < self.width && pos.1 < self.height).then(|| self[pos])
+    }
+
+    /// Whether `pos` is inside the grid and not a wall.
+    pub fn is_open(&self, (x, y): Position) -> bool {
+        x < self.width && y < self.height && !self.walls.get(y * self.width + x)
+    }
+
+    pub(super) fn set(&mut self, (x, y): Position, ch: char) {
+        let i = y * self.width + x;
+        self.walls.set(i, ch == '#');
+        if ch == '#' || ch == ' ' {
+            if !self.marks.is_empty() {
+                self.marks.remove(&i);
+            }
+        } else {
+            self.marks.insert(i, ch);
+        }
+    }
+
+    /// Open tiles holding something other than `' '`, in no particular order.
+    pub(super) fn marked(&self) -> impl Iterator<Item = (Position, char)> + '_ {
+        self.marks.iter().map(|(&i, &ch)| ((i % self.width, i / self.width), ch))
+    }
+
+    /// The characters of row `y`, left to right.
+    pub fn row(&self, y: usize) -> impl Iterator<Item = char> + '_ {
+        (0..self.width).map(move |x| self[(x, y)])
+    }
+}
+
+impl Index<Position> for TileGrid {
+    type Output = char;
+
+    fn index(&self, (x, y): Position) -> &char {
+        assert!(x < self.width && y < self.height, "grid position ({x}, {y}) is outside the grid");
+        let i = y * self.width + x;
+        if self.walls.get(i) {
+            &'#'
+        } else {
+            self.marks.get(&i).unwrap_or(&' ')
+        }
+    }
+}
+
+/// Walls and passages of the cells, stored column by column: one wall bit and one byte of
+/// passage directions per cell, with directions numbered as in [`Maze::cell_in_direction`](super::Maze).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct CellStore {
+    height: usize,
+    walls: BitSet,
+    links: Vec<u8>,
+}
+
+impl CellStore {
+    /// Cells that are all solid wall, with no passages.
+    pub(super) fn new(width: usize, height: usize) -> Self {
+        CellStore { height, walls: BitSet::new(width * height, true), links: vec![0; width * height] }
+    }
+
+    pub(super) fn is_wall(&self, (x, y): Position) -> bool {
+        self.walls.get(x * self.height + y)
+    }
+
+    pub(super) fn set_wall(&mut self, (x, y): Position, wall: bool) {
+        self.walls.set(x * self.height + y, wall);
+    }
+
+    /// Passage directions out of a cell, one bit per direction.
+    pub(super) fn links(&self, (x, y): Position) -> u8 {
+        self.links[x * self.height + y]
+    }
+
+    pub(super) fn set_link(&mut self, (x, y): Position, direction: usize, linked: bool) {
+        let links = &mut self.links[x * self.height + y];
+        if linked {
+            *links |= 1 << direction;
+        } else {
+            *links &= !(1 << direction);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//terrain.rs
+
+use super::{Maze, PlacementError, Position, STAIRS_BOTH, STAIRS_DOWN, STAIRS_UP};
+use ::rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Surface of an open tile, with the cost of stepping onto it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Terrain {
+    Open,
+    Road,
+    Mud,
+    Water,
+}
+
+impl Terrain {
+    pub const ALL: [Terrain; 4] = [Terrain::Open, Terrain::Road, Terrain::Mud, Terrain::Water];
+
+    /// The grid character for this terrain.
+    pub fn symbol(self) -> char {
+        match self {
+            Terrain::Open => ' ',
+            Terrain::Road => '.',
+            Terrain::Mud => ',',
+            Terrain::Water => '=',
+        }
+    }
+
+    pub fn from_symbol(ch: char) -> Option<Terrain> {
+        Terrain::ALL.into_iter().find(|terrain| terrain.symbol() == ch)
+    }
+
+    /// Movement cost of one step onto this terrain. Plain open tiles cost 1.
+    pub fn cost(self) -> f64 {
+        match self {
+            Terrain::Open => 1.0,
+            Terrain::Road => 0.5,
+            Terrain::Mud => 3.0,
+            Terrain::Water => 5.0,
+        }
+    }
+}
+
+impl Maze {
+    /// The terrain of an open tile, or `None` for walls and positions outside the grid.
+    /// Start, goal and other marker tiles count as [`Terrain::Open`].
+    pub fn terrain_at(&self, pos: Position) -> Option<Terrain> {
+        match self.grid.get(pos)? {
+            '#' => None,
+            ch => Some(Terrain::from_symbol(ch).unwrap_or(Terrain::Open)),
+        }
+    }
+
+    /// Movement cost of stepping onto `pos`, or `None` if it is not passable.
+    pub fn step_cost(&self, pos: Position) -> Option<f64> {
+        self.terrain_at(pos).map(Terrain::cost)
+    }
+
+    /// Paints one open tile with the given terrain. The start, goal, stairs and doors cannot be painted.
+    pub fn set_terrain(&mut self, (x, y): Position, terrain: Terrain) -> Result<(), PlacementError> {
+        if y >= self.grid.height() || x >= self.grid.width() {
+            return Err(PlacementError::OutOfBounds((x, y)));
+        }
+        if Some((x, y)) == self.start_pos
+            || Some((x, y)) == self.end_pos
+            || self.doors.iter().any(|door| door.position == (x, y))
+        {
+            return Err(PlacementError::Occupied((x, y)));
+        }
+        if self.is_border_tile((x, y)) || matches!(self.grid[(x, y)], '#' | STAIRS_UP | STAIRS_DOWN | STAIRS_BOTH) {
+            return Err(PlacementError::Blocked((x, y)));
+        }
+
+        self.grid.set((x, y), terrain.symbol());
+        Ok(())
+    }
+
+    /// Paints `patches` blobs of terrain, each covering the open tiles within `radius` steps
+    /// of a random open tile. Returns the number of tiles painted.
+    pub fn add_terrain_patches(&mut self, terrain: Terrain, patches: usize, radius: usize, rng: &mut dyn RngCore) -> usize {
+        let open: Vec<Position> = self.open_tiles().collect();
+        if open.is_empty() {
+            return 0;
+        }
+
+        let mut painted = 0;
+        for _ in 0..patches {
+            let center = open[rng.gen_range(0..open.len())];
+            let mut seen = vec![vec![false; self.grid.width()]; self.grid.height()];
+            let mut queue = VecDeque::from([(center, 0)]);
+            seen[center.1][center.0] = true;
+
+            while let Some(((x, y), distance)) = queue.pop_front() {
+                if self.set_terrain((x, y), terrain).is_ok() {
+                    painted += 1;
+                }
+                if distance == radius {
+                    continue;
+                }
+
+                for next in self.open_neighbors((x, y)) {
+                    if self.is_border_tile(next) || seen[next.1][next.0] {
+                        continue;
+                    }
+                    seen[next.1][next.0] = true;
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        painted
+    }
+
+    /// Open tiles inside the outer wall, excluding the start and goal.
+    pub(super) fn open_tiles(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.grid.height())
+            .flat_map(move |y| (0..self.grid.width()).map(move |x| (x, y)))
+            .filter(move |&(x, y)| {
+                !self.is_border_tile((x, y))
+                    && self.grid[(x, y)] != '#'
+                    && Some((x, y)) != self.start_pos
+                    && Some((x, y)) != self.end_pos
+            })
+    }
+}
@@ -0,0 +1,301 @@
+//topology.rs
+
+use super::{Maze, Position};
+use serde::{Deserialize, Serialize};
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// Bits needed to write any of `count` moves as a binary number.
+pub(super) fn bits_for(count: usize) -> usize {
+    (usize::BITS - (count - 1).leading_zeros()) as usize
+}
+
+/// The shape of the cells and which cells touch.
+///
+/// Square mazes use the character grid described on [`Maze`], where walls and passages are
+/// tiles of their own. Hex and triangle mazes have one grid character per cell, walls live
+/// only in the cell graph, and positions are cell coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Topology {
+    #[default]
+    Square,
+    /// Pointy-top hexagons in offset rows, odd rows shifted half a cell right.
+    /// Moves are 0 north-east, 1 east, 2 south-east, 3 south-west, 4 west, 5 north-west.
+    Hex,
+    /// Alternating up- and down-pointing triangles; cell `(x, y)` points up when `x + y` is even.
+    /// Moves are 0 through the flat side, 1 east, 2 west.
+    /// A single column of more than two triangles is not connected, so these mazes need a width of at least 2.
+    Triangle,
+}
+
+impl Topology {
+    /// Number of distinct moves from a cell.
+    pub fn directions(self) -> usize {
+        match self {
+            Topology::Square => 4,
+            Topology::Hex => 6,
+            Topology::Triangle => 3,
+        }
+    }
+
+    /// Bits needed per move in a binary genome, for use as the GA's `gene_length`.
+    pub fn gene_length(self) -> usize {
+        bits_for(self.directions())
+    }
+
+    /// The cell reached by moving `direction` from `(x, y)`, ignoring walls and bounds.
+    fn offset(self, (x, y): Position, direction: usize) -> (isize, isize) {
+        let (x, y) = (x as isize, y as isize);
+        match self {
+            Topology::Square => match direction {
+                0 => (x, y - 1),
+                1 => (x + 1, y),
+                2 => (x, y + 1),
+                _ => (x - 1, y),
+            },
+            Topology::Hex => {
+                let shift = y & 1;
+                match direction {
+                    0 => (x + shift, y - 1),
+                    1 => (x + 1, y),
+                    2 => (x + shift, y + 1),
+                    3 => (x + shift - 1, y + 1),
+                    4 => (x - 1, y),
+                    _ => (x + shift - 1, y - 1),
+                }
+            }
+            Topology::Triangle => match direction {
+                0 if (x + y) % 2 == 0 => (x, y + 1),
+                0 => (x, y - 1),
+                1 => (x + 1, y),
+                _ => (x - 1, y),
+            },
+        }
+    }
+
+    /// Columns and rows of the character grid for a maze of the given size.
+    pub(super) fn grid_size(self, dimension_x: usize, dimension_y: usize) -> (usize, usize) {
+        match self {
+            Topology::Square => (dimension_x * 2 + 1, dimension_y * 2 + 1),
+            _ => (dimension_x, dimension_y),
+        }
+    }
+
+    /// Size of a `dimension_x` by `dimension_y` maze in drawing units, where a grid tile or
+    /// hex cell is one unit wide.
+    pub fn canvas_size(self, dimension_x: usize, dimension_y: usize) -> (f32, f32) {
+        match self {
+            Topology::Square => ((dimension_x * 2 + 1) as f32, (dimension_y * 2 + 1) as f32),
+            Topology::Hex => {
+                let radius = 1.0 / SQRT_3;
+                (dimension_x as f32 + 0.5, 2.0 * radius + dimension_y.saturating_sub(1) as f32 * 1.5 * radius)
+            }
+            Topology::Triangle => ((dimension_x + 1) as f32 / 2.0, dimension_y as f32 * SQRT_3 / 2.0),
+        }
+    }
+}
+
+impl Maze {
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Size of the maze in drawing units (see [`Topology::canvas_size`]).
+    pub fn canvas_size(&self) -> (f32, f32) {
+        match self.topology {
+            Topology::Square => (self.grid.width() as f32, self.grid.height() as f32),
+            topology => topology.canvas_size(self.dimension_x, self.dimension_y),
+        }
+    }
+
+    /// The grid position of a cell.
+    pub fn cell_to_tile(&self, (x, y): Position) -> Position {
+        match self.topology {
+            Topology::Square => (x * 2 + 1, y * 2 + 1),
+            _ => (x, y),
+        }
+    }
+
+    /// The cell at a grid position, if the position is a cell rather than a wall or passage tile.
+    pub fn tile_to_cell(&self, (x, y): Position) -> Option<Position> {
+        let cell = match self.topology {
+            Topology::Square if x % 2 == 1 && y % 2 == 1 => (x / 2, y / 2),
+            Topology::Square => return None,
+            _ => (x, y),
+        };
+        (cell.0 < self.dimension_x && cell.1 < self.dimension_y).then_some(cell)
+    }
+
+    /// The cell next to `cell` in `direction`, numbered as in [`Topology::offset`]. Square cells
+    /// stay on their own level, wrap around the edges of a wrapping maze, and use 4 and 5 for
+    /// the cells directly above and below.
+    pub(super) fn cell_in_direction(&self, cell: Position, direction: usize) -> Option<Position> {
+        if self.topology == Topology::Square && direction >= 4 {
+            let level = self.cell_level(cell);
+            return match direction {
+                4 if level + 1 < self.levels => Some((cell.0 + self.level_width(), cell.1)),
+                5 if level > 0 => Some((cell.0 - self.level_width(), cell.1)),
+                _ => None,
+            };
+        }
+
+        let (mut nx, mut ny) = self.topology.offset(cell, direction);
+        if self.topology == Topology::Square && self.wrap {
+            nx = nx.rem_euclid(self.dimension_x as isize);
+            ny = ny.rem_euclid(self.dimension_y as isize);
+        }
+        let inside = nx >= 0 && ny >= 0 && (nx as usize) < self.dimension_x && (ny as usize) < self.dimension_y;
+        let next = (nx as usize, ny as usize);
+        (inside && (self.levels == 1 || self.cell_level(next) == self.cell_level(cell))).then_some(next)
+    }
+
+    /// The direction that leads from cell `from` to cell `to`, if they are adjacent.
+    pub(super) fn direction_to(&self, from: Position, to: Position) -> Option<usize> {
+        (0..self.directions()).find(|&direction| self.cell_in_direction(from, direction) == Some(to))
+    }
+
+    /// Where a single move from `pos` ends up, or `None` if it runs into a wall or off the grid.
+    ///
+    /// Square moves are 0 up, 1 right, 2 down, 3 left, and multi-level mazes add 4 and 5 for
+    /// the stairs up and down. Hex moves 0 to 5 and triangle moves 0 to 2 each cross one side
+    /// of the cell. Any value from [`Maze::directions`] up stays in place: folding those values
+    /// onto real moves would make some moves more likely than others when a bit genome has
+    /// more codes than the maze has moves, such as 3-bit genes on a hex maze.
+    pub fn step(&self, pos: Position, direction: u8) -> Option<Position> {
+        if direction as usize >= self.directions() {
+            return self.grid.is_open(pos).then_some(pos);
+        }
+
+        let next = match self.topology {
+            Topology::Square if self.levels > 1 && direction >= 4 => self.stair_step(pos, direction == 4)?,
+            Topology::Square => {
+                let (dx, dy) = match direction {
+                    0 => (0, -1),
+                    1 => (1, 0),
+                    2 => (0, 1),
+                    _ => (-1, 0),
+                };
+                let (x, y) = self.wrap_coordinate((pos.0 as isize + dx, pos.1 as isize + dy));
+                (x as usize, y as usize)
+            }
+            _ => {
+                let direction = direction as usize;
+                if self.cells.links(pos) & (1 << direction) == 0 {
+                    return None;
+                }
+                self.cell_in_direction(pos, direction)?
+            }
+        };
+
+        self.grid.is_open(next).then_some(next)
+    }
+
+    /// Grid positions reachable from `pos` in one move, in the same order as `adjacent`.
+    pub(super) fn open_neighbors(&self, pos: Position) -> Vec<Position> {
+        let directions: Vec<u8> = match self.topology {
+            Topology::Square if self.levels > 1 => vec![1, 2, 3, 0, 4, 5],
+            Topology::Square => vec![1, 2, 3, 0],
+            topology => (0..topology.directions() as u8).collect(),
+        };
+        directions.into_iter().filter_map(|direction| self.step(pos, direction)).collect()
+    }
+
+    /// The polygon covering a grid position, in drawing units (see [`Topology::canvas_size`]).
+    pub fn tile_shape(&self, (x, y): Position) -> Vec<(f32, f32)> {
+        let (fx, fy) = (x as f32, y as f32);
+        match self.topology {
+            Topology::Square => vec![(fx, fy), (fx + 1.0, fy), (fx + 1.0, fy + 1.0), (fx, fy + 1.0)],
+            Topology::Hex => {
+                let radius = 1.0 / SQRT_3;
+                let (cx, cy) = self.tile_center((x, y));
+                (0..6)
+                    .map(|corner| {
+                        let angle = (30.0 + 60.0 * corner as f32).to_radians();
+                        (cx + radius * angle.cos(), cy + radius * angle.sin())
+                    })
+                    .collect()
+            }
+            Topology::Triangle => {
+                let height = SQRT_3 / 2.0;
+                let left = fx / 2.0;
+                let (top, bottom) = (fy * height, (fy + 1.0) * height);
+                if (x + y) % 2 == 0 {
+                    vec![(left, bottom), (left + 1.0, bottom), (left + 0.5, top)]
+                } else {
+                    vec![(left, top), (left + 1.0, top), (left + 0.5, bottom)]
+                }
+            }
+        }
+    }
+
+    /// The centre of a grid position, in drawing units.
+    pub fn tile_center(&self, (x, y): Position) -> (f32, f32) {
+        match self.topology {
+            Topology::Square => (x as f32 + 0.5, y as f32 + 0.5),
+            Topology::Hex => {
+                let radius = 1.0 / SQRT_3;
+                let shift = if y % 2 == 1 { 0.5 } else { 0.0 };
+                (x as f32 + 0.5 + shift, radius + y as f32 * 1.5 * radius)
+            }
+            Topology::Triangle => {
+                let shape = self.tile_shape((x, y));
+                let n = shape.len() as f32;
+                (shape.iter().map(|p| p.0).sum::<f32>() / n, shape.iter().map(|p| p.1).sum::<f32>() / n)
+            }
+        }
+    }
+
+    /// Line segments for the walls between cells, in drawing units.
+    ///
+    /// Square mazes draw walls as grid tiles, so this is empty for them.
+    pub fn wall_segments(&self) -> Vec<((f32, f32), (f32, f32))> {
+        if self.topology == Topology::Square {
+            return Vec::new();
+        }
+
+        let mut segments = Vec::new();
+        for x in 0..self.dimension_x {
+            for y in 0..self.dimension_y {
+                let shape = self.tile_shape((x, y));
+                for direction in 0..self.topology.directions() {
+                    let neighbor = self.cell_in_direction((x, y), direction);
+                    let joined = self.cells.links((x, y)) & (1 << direction) != 0;
+                    if joined || neighbor.is_some_and(|n| n < (x, y)) {
+                        continue;
+                    }
+
+                    let edge = match self.topology {
+                        Topology::Hex => (direction + 4) % 6,
+                        _ => direction,
+                    };
+                    segments.push((shape[edge], shape[(edge + 1) % shape.len()]));
+                }
+            }
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::{Maze, RecursiveBacktracker, Topology};
+
+    #[test]
+    fn codes_past_the_last_move_stay_in_place() {
+        for (topology, directions) in [(Topology::Hex, 6), (Topology::Triangle, 3)] {
+            let maze = Maze::with_topology(6, 6, topology, &RecursiveBacktracker, 1);
+            for y in 0..6 {
+                for x in 0..6 {
+                    for code in directions..8 {
+                        assert_eq!(maze.step((x, y), code), Some((x, y)));
+                    }
+                }
+            }
+        }
+
+        let maze = Maze::with_levels(4, 4, 2, &RecursiveBacktracker, 1);
+        let start = maze.start_pos().unwrap();
+        assert_eq!(maze.step(start, 6), Some(start));
+        assert_eq!(maze.step(start, 7), Some(start));
+    }
+}
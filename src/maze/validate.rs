@@ -0,0 +1,377 @@
+//validate.rs
+
+use super::{Adversary, Maze, PlacementError, Position, STAIRS_BOTH, STAIRS_DOWN, STAIRS_UP};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// One way a maze breaks the invariants the rest of the crate relies on.
+///
+/// Cell violations use cell coordinates; tile violations use grid coordinates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// A cell lists a neighbour outside the maze or one it does not share a side with.
+    NotAdjacent { cell: Position, neighbor: Position },
+    /// A cell lists a neighbour that does not list it back.
+    OneWayLink { cell: Position, neighbor: Position },
+    /// A wall cell has passages.
+    LinkedWall { cell: Position },
+    /// An open cell cannot be reached from the start along the cell graph.
+    UnreachableCell { cell: Position },
+    /// A grid character disagrees with the cell graph: `expected` is what the graph draws there.
+    GridMismatch { tile: Position, expected: char, found: char },
+    /// A tile of the outer wall is open without holding the start or goal.
+    OpenBorder { tile: Position },
+    MissingStart,
+    MissingGoal,
+    BadStart(PlacementError),
+    BadGoal(PlacementError),
+    /// The goal cannot be reached by any route from the start.
+    GoalUnreachable,
+    UnreachableWaypoint { waypoint: Position },
+    /// A scheduled door that is not on a passage tile between two cells.
+    BadDoor { tile: Position },
+    /// A lock key or locked door outside the grid or on a wall.
+    BadLock { tile: Position },
+    BadHazard { tile: Position },
+    /// A patrol tile or chaser start outside the grid or on a wall.
+    BadAdversary { tile: Position },
+    BadWaypoint { tile: Position },
+    /// The goal can be reached, but every way there runs over a hazard or through a locked
+    /// door whose key is out of reach.
+    NoSafeRoute,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::NotAdjacent { cell, neighbor } => write!(f, "cell {cell:?} is linked to {neighbor:?}, which is not next to it"),
+            Violation::OneWayLink { cell, neighbor } => write!(f, "cell {cell:?} is linked to {neighbor:?} but not the other way"),
+            Violation::LinkedWall { cell } => write!(f, "wall cell {cell:?} has passages"),
+            Violation::UnreachableCell { cell } => write!(f, "open cell {cell:?} cannot be reached from the start"),
+            Violation::GridMismatch { tile, expected, found } => {
+                write!(f, "grid has {found:?} at {tile:?} where the cells give {expected:?}")
+            }
+            Violation::OpenBorder { tile } => write!(f, "outer wall is open at {tile:?}"),
+            Violation::MissingStart => write!(f, "the maze has no start"),
+            Violation::MissingGoal => write!(f, "the maze has no goal"),
+            Violation::BadStart(err) => write!(f, "invalid start: {err}"),
+            Violation::BadGoal(err) => write!(f, "invalid goal: {err}"),
+            Violation::GoalUnreachable => write!(f, "the goal cannot be reached from the start"),
+            Violation::UnreachableWaypoint { waypoint } => write!(f, "waypoint {waypoint:?} cannot be reached from the start"),
+            Violation::BadDoor { tile } => write!(f, "door at {tile:?} is not on a passage tile between two cells"),
+            Violation::BadLock { tile } => write!(f, "lock at {tile:?} is outside the grid or on a wall"),
+            Violation::BadHazard { tile } => write!(f, "hazard at {tile:?} is outside the grid or on a wall"),
+            Violation::BadAdversary { tile } => write!(f, "adversary at {tile:?} is outside the grid or on a wall"),
+            Violation::BadWaypoint { tile } => write!(f, "waypoint at {tile:?} is outside the grid or on a wall"),
+            Violation::NoSafeRoute => write!(f, "every way to the goal runs over a hazard or through a locked door without its key"),
+        }
+    }
+}
+
+fn is_stairs(ch: char) -> bool {
+    matches!(ch, STAIRS_UP | STAIRS_DOWN | STAIRS_BOTH)
+}
+
+impl Maze {
+    /// Checks the maze for broken invariants and lists every one found, or nothing for a
+    /// sound maze.
+    ///
+    /// Generated mazes always pass; this is meant for imported and hand-edited ones, before
+    /// they reach code that assumes a consistent cell graph and grid. Reachability is only
+    /// checked once the cell graph itself is sound, and treats scheduled and locked doors and
+    /// hazards as open before checking for a safe route separately. Doors, locks, hazards,
+    /// adversaries and waypoints must sit on open tiles inside the grid.
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut violations = self.link_violations();
+        let graph_sound = violations.is_empty();
+
+        let endpoints = self.endpoint_violations();
+        let start_placed = !endpoints.iter().any(|v| matches!(v, Violation::MissingStart | Violation::BadStart(_)));
+        violations.extend(self.grid_violations());
+        violations.extend(endpoints);
+        violations.extend(self.item_violations());
+
+        if graph_sound && start_placed && let Some(start) = self.start_pos {
+            violations.extend(self.unreachable_cells(start));
+
+            let seen = self.reachable_tiles(&[]).unwrap_or_default();
+            let reached = |(x, y): Position| seen.get(y).and_then(|row| row.get(x)).copied().unwrap_or(false);
+            if self.end_pos.is_some_and(|goal| !reached(goal)) {
+                violations.push(Violation::GoalUnreachable);
+            }
+            let mut blocked = false;
+            for &waypoint in &self.waypoints {
+                if !reached(waypoint) {
+                    violations.push(Violation::UnreachableWaypoint { waypoint });
+                    blocked = true;
+                }
+            }
+            if !blocked && self.end_pos.is_some_and(reached) && self.solve_bfs().is_none() {
+                violations.push(Violation::NoSafeRoute);
+            }
+        }
+
+        violations
+    }
+
+    fn link_violations(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for x in 0..self.dimension_x {
+            for y in 0..self.dimension_y {
+                let here = (x, y);
+                if self.cells.is_wall(here) && self.degree(here) > 0 {
+                    violations.push(Violation::LinkedWall { cell: here });
+                }
+
+                for neighbor in self.cell_neighbors(here) {
+                    if !self.adjacent(here).any(|next| next == neighbor) {
+                        violations.push(Violation::NotAdjacent { cell: here, neighbor });
+                    } else if !self.is_linked(neighbor, here) {
+                        violations.push(Violation::OneWayLink { cell: here, neighbor });
+                    }
+                }
+            }
+        }
+
+        violations
+    }
+
+    /// Compares the grid with the one the cell graph draws. Start and goal tiles are left to
+    /// [`Maze::endpoint_violations`], and the outer wall is checked on its own.
+    fn grid_violations(&self) -> Vec<Violation> {
+        let expected_grid = self.render_grid();
+        let mut violations = Vec::new();
+
+        for y in 0..self.grid.height() {
+            for (x, found) in self.grid.row(y).enumerate() {
+                let tile = (x, y);
+                if Some(tile) == self.start_pos || Some(tile) == self.end_pos {
+                    continue;
+                }
+                if self.is_border_tile(tile) {
+                    if found != '#' {
+                        violations.push(Violation::OpenBorder { tile });
+                    }
+                    continue;
+                }
+
+                let expected = expected_grid[tile];
+                let matches = if is_stairs(expected) || is_stairs(found) { expected == found } else { (expected == '#') == (found == '#') };
+                if !matches {
+                    violations.push(Violation::GridMismatch { tile, expected, found });
+                }
+            }
+        }
+
+        violations
+    }
+
+    fn endpoint_violations(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        match self.start_pos {
+            Some(start) => violations.extend(self.check_placeable(start).err().map(Violation::BadStart)),
+            None => violations.push(Violation::MissingStart),
+        }
+        match self.end_pos {
+            Some(goal) if Some(goal) == self.start_pos => violations.push(Violation::BadGoal(PlacementError::SamePosition(goal))),
+            Some(goal) => violations.extend(self.check_placeable(goal).err().map(Violation::BadGoal)),
+            None => violations.push(Violation::MissingGoal),
+        }
+
+        violations
+    }
+
+    /// Doors, locks, hazards, adversaries and waypoints that are off the grid or on a wall.
+    fn item_violations(&self) -> Vec<Violation> {
+        let off_floor = |tile: Position| self.grid.get(tile).is_none_or(|ch| ch == '#');
+        let mut violations: Vec<Violation> = self
+            .doors
+            .iter()
+            .filter(|door| self.door_cells(door.position).is_none())
+            .map(|door| Violation::BadDoor { tile: door.position })
+            .collect();
+
+        for lock in &self.locks {
+            violations.extend([lock.key, lock.door].into_iter().filter(|&tile| off_floor(tile)).map(|tile| Violation::BadLock { tile }));
+        }
+        violations.extend(self.hazards.iter().copied().filter(|&tile| off_floor(tile)).map(|tile| Violation::BadHazard { tile }));
+        for adversary in &self.adversaries {
+            let tiles = match adversary {
+                Adversary::Patrol { route } => route.clone(),
+                Adversary::Chaser { start, .. } => vec![*start],
+            };
+            violations.extend(tiles.into_iter().filter(|&tile| off_floor(tile)).map(|tile| Violation::BadAdversary { tile }));
+        }
+        violations.extend(self.waypoints.iter().copied().filter(|&tile| off_floor(tile)).map(|tile| Violation::BadWaypoint { tile }));
+
+        violations
+    }
+
+    /// Open cells the cell graph does not join to the cell the start sits on or opens into.
+    fn unreachable_cells(&self, start: Position) -> Vec<Violation> {
+        let door_links: Vec<(Position, Position)> = self.doors.iter().filter_map(|door| self.door_cells(door.position)).collect();
+        let from = self.nearest_cell(start);
+        let mut seen = vec![vec![false; self.dimension_y]; self.dimension_x];
+        let mut queue = VecDeque::from([from]);
+        seen[from.0][from.1] = true;
+
+        while let Some(cell) = queue.pop_front() {
+            let through_doors = door_links.iter().filter_map(|&(a, b)| {
+                if cell == a {
+                    Some(b)
+                } else if cell == b {
+                    Some(a)
+                } else {
+                    None
+                }
+            });
+            for next in self.cell_neighbors(cell).chain(through_doors) {
+                if !seen[next.0][next.1] {
+                    seen[next.0][next.1] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        self.open_cells()
+            .filter(|&(x, y)| !seen[x][y])
+            .map(|cell| Violation::UnreachableCell { cell })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{Door, Kruskals, Lock, RecursiveBacktracker, Topology, Wilsons};
+
+    /// Cells (0, 0), (1, 0), (1, 1) and (0, 1) joined in a U from the start to the goal.
+    const SMALL: &str = "#####\n*   #\n### #\n~   #\n#####\n";
+
+    fn small() -> Maze {
+        Maze::from_ascii(SMALL).unwrap()
+    }
+
+    #[test]
+    fn generated_mazes_are_sound() {
+        assert!(small().validate().is_empty());
+        for seed in 0..5 {
+            assert!(Maze::with_generator(9, 7, &RecursiveBacktracker, seed).validate().is_empty());
+            assert!(Maze::with_topology(8, 6, Topology::Hex, &Wilsons, seed).validate().is_empty());
+            assert!(Maze::with_topology(8, 6, Topology::Triangle, &Kruskals, seed).validate().is_empty());
+            assert!(Maze::with_levels(6, 4, 2, &RecursiveBacktracker, seed).validate().is_empty());
+            assert!(Maze::with_wraparound(6, 4, &Wilsons, seed).validate().is_empty());
+        }
+    }
+
+    #[test]
+    fn reports_missing_start_and_goal() {
+        let mut maze = small();
+        maze.start_pos = None;
+        maze.end_pos = None;
+        let violations = maze.validate();
+        assert!(violations.contains(&Violation::MissingStart));
+        assert!(violations.contains(&Violation::MissingGoal));
+    }
+
+    #[test]
+    fn reports_start_and_goal_on_walls() {
+        let mut maze = small();
+        maze.start_pos = Some((2, 2));
+        maze.end_pos = Some((9, 9));
+        let violations = maze.validate();
+        assert!(violations.contains(&Violation::BadStart(PlacementError::Blocked((2, 2)))));
+        assert!(violations.contains(&Violation::BadGoal(PlacementError::OutOfBounds((9, 9)))));
+    }
+
+    #[test]
+    fn reports_goal_on_the_start() {
+        let mut maze = small();
+        maze.end_pos = maze.start_pos;
+        assert!(maze.validate().contains(&Violation::BadGoal(PlacementError::SamePosition((0, 1)))));
+    }
+
+    #[test]
+    fn reports_one_way_links() {
+        let mut maze = small();
+        maze.cells.set_link((0, 0), 1, false);
+        assert!(maze.validate().contains(&Violation::OneWayLink { cell: (1, 0), neighbor: (0, 0) }));
+    }
+
+    #[test]
+    fn reports_linked_walls() {
+        let mut maze = small();
+        maze.cells.set_wall((1, 1), true);
+        assert!(maze.validate().contains(&Violation::LinkedWall { cell: (1, 1) }));
+    }
+
+    #[test]
+    fn reports_grid_mismatches_and_open_borders() {
+        let mut maze = small();
+        maze.grid.set((3, 2), '#');
+        maze.grid.set((2, 4), ' ');
+        let violations = maze.validate();
+        assert!(violations.contains(&Violation::GridMismatch { tile: (3, 2), expected: ' ', found: '#' }));
+        assert!(violations.contains(&Violation::OpenBorder { tile: (2, 4) }));
+    }
+
+    #[test]
+    fn reports_unreachable_cells_and_goal() {
+        let maze = Maze::from_ascii("#####\n*   #\n#####\n~   #\n#####\n").unwrap();
+        let violations = maze.validate();
+        assert!(violations.contains(&Violation::UnreachableCell { cell: (0, 1) }));
+        assert!(violations.contains(&Violation::UnreachableCell { cell: (1, 1) }));
+        assert!(violations.contains(&Violation::GoalUnreachable));
+    }
+
+    #[test]
+    fn reports_unreachable_waypoints() {
+        let mut maze = Maze::from_ascii("#######\n*   # #\n### # #\n~   # #\n#######\n").unwrap();
+        maze.waypoints = vec![(5, 2)];
+        assert!(maze.validate().contains(&Violation::UnreachableWaypoint { waypoint: (5, 2) }));
+    }
+
+    #[test]
+    fn reports_a_goal_only_reachable_over_hazards() {
+        let mut maze = small();
+        maze.hazards = vec![(3, 2)];
+        assert_eq!(maze.validate(), vec![Violation::NoSafeRoute]);
+    }
+
+    #[test]
+    fn reports_doors_off_passage_tiles() {
+        let mut maze = small();
+        maze.doors = vec![Door { position: (1, 1), period: 2, open_for: 1, phase: 0 }];
+        assert!(maze.validate().contains(&Violation::BadDoor { tile: (1, 1) }));
+    }
+
+    #[test]
+    fn reports_locks_on_walls() {
+        let mut maze = small();
+        maze.locks = vec![Lock { key: (2, 1), door: (2, 2) }];
+        assert!(maze.validate().contains(&Violation::BadLock { tile: (2, 2) }));
+    }
+
+    #[test]
+    fn reports_hazards_outside_the_grid() {
+        let mut maze = small();
+        maze.hazards = vec![(7, 1)];
+        assert!(maze.validate().contains(&Violation::BadHazard { tile: (7, 1) }));
+    }
+
+    #[test]
+    fn reports_adversaries_on_walls() {
+        let mut maze = small();
+        maze.adversaries = vec![Adversary::Chaser { start: (0, 0), period: 1 }];
+        assert!(maze.validate().contains(&Violation::BadAdversary { tile: (0, 0) }));
+    }
+
+    #[test]
+    fn reports_waypoints_outside_the_grid() {
+        let mut maze = small();
+        maze.waypoints = vec![(1, 9)];
+        assert!(maze.validate().contains(&Violation::BadWaypoint { tile: (1, 9) }));
+    }
+}
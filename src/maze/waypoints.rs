@@ -0,0 +1,99 @@
+//waypoints.rs
+
+use super::{Maze, PlacementError, Position};
+use serde::{Deserialize, Serialize};
+
+/// Whether waypoints must be collected in the order they were given.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WaypointOrder {
+    /// Checkpoints: only the next uncollected waypoint in the list counts.
+    #[default]
+    Ordered,
+    /// Multiple goals: any uncollected waypoint counts when stepped on.
+    Unordered,
+}
+
+impl Maze {
+    /// Sets the waypoints a route has to collect before the goal counts as reached.
+    ///
+    /// Waypoints must be distinct open tiles inside the outer wall and may not sit on the start,
+    /// the goal or a hazard. An empty list restores the plain start-to-goal behaviour.
+    pub fn set_waypoints(&mut self, waypoints: Vec<Position>, order: WaypointOrder) -> Result<(), PlacementError> {
+        for (i, &(x, y)) in waypoints.iter().enumerate() {
+            if y >= self.grid.height() || x >= self.grid.width() {
+                return Err(PlacementError::OutOfBounds((x, y)));
+            }
+            if self.is_border_tile((x, y)) || self.grid[(x, y)] == '#' {
+                return Err(PlacementError::Blocked((x, y)));
+            }
+            if Some((x, y)) == self.start_pos
+                || Some((x, y)) == self.end_pos
+                || waypoints[..i].contains(&(x, y))
+                || self.hazards.contains(&(x, y))
+            {
+                return Err(PlacementError::Occupied((x, y)));
+            }
+        }
+
+        self.waypoints = waypoints;
+        self.waypoint_order = order;
+        Ok(())
+    }
+
+    pub fn waypoints(&self) -> &[Position] {
+        &self.waypoints
+    }
+
+    pub fn waypoint_order(&self) -> WaypointOrder {
+        self.waypoint_order
+    }
+
+    /// For each waypoint, the index into `path` at which it was collected, if it was.
+    pub fn waypoint_pickups(&self, path: &[Position]) -> Vec<Option<usize>> {
+        let mut pickups = vec![None; self.waypoints.len()];
+        let mut collected = vec![false; self.waypoints.len()];
+
+        for (step, &pos) in path.iter().enumerate() {
+            if let Some(i) = self.collectable_waypoint(pos, &collected) {
+                collected[i] = true;
+                pickups[i] = Some(step);
+            }
+        }
+
+        pickups
+    }
+
+    /// The waypoint collected by stepping on `pos`, given which ones are already collected.
+    pub(super) fn collectable_waypoint(&self, pos: Position, collected: &[bool]) -> Option<usize> {
+        match self.waypoint_order {
+            WaypointOrder::Ordered => collected
+                .iter()
+                .position(|&done| !done)
+                .filter(|&next| self.waypoints[next] == pos),
+            WaypointOrder::Unordered => self
+                .waypoints
+                .iter()
+                .enumerate()
+                .position(|(i, &waypoint)| !collected[i] && waypoint == pos),
+        }
+    }
+
+    /// Where a route at `pos` should head next: the next waypoint, or the goal once all are collected.
+    pub(super) fn next_target(&self, pos: Position, collected: &[bool]) -> Option<Position> {
+        match self.waypoint_order {
+            WaypointOrder::Ordered => collected
+                .iter()
+                .position(|&done| !done)
+                .map(|next| self.waypoints[next])
+                .or(self.end_pos),
+            WaypointOrder::Unordered => self
+                .waypoints
+                .iter()
+                .zip(collected)
+                .filter(|&(_, &done)| !done)
+                .map(|(&waypoint, _)| waypoint)
+                .min_by_key(|&waypoint| self.grid_distance(pos, waypoint))
+                .or(self.end_pos),
+        }
+    }
+}
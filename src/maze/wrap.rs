@@ -0,0 +1,39 @@
+//wrap.rs
+
+use super::{Maze, Position, TileGrid};
+
+impl Maze {
+    /// Whether the maze is toroidal: moving off one edge comes back in on the opposite edge.
+    ///
+    /// A wrapping maze has no outer wall. Its grid is `2 * dimension_x` by `2 * dimension_y`,
+    /// with row and column 0 holding the passages and walls between the last and first cells.
+    pub fn wraps(&self) -> bool {
+        self.wrap
+    }
+
+    /// Manhattan distance between two grid positions, taking the short way around a wrapping maze.
+    pub fn grid_distance(&self, a: Position, b: Position) -> usize {
+        let dx = a.0.abs_diff(b.0);
+        let dy = a.1.abs_diff(b.1);
+        if !self.wrap {
+            return dx + dy;
+        }
+
+        let (width, height) = (self.grid.width(), self.grid.height());
+        dx.min(width - dx) + dy.min(height - dy)
+    }
+
+    /// Turns a blank square maze into a wrapping one by dropping the outer wall's last row and column.
+    pub(super) fn make_wrapping(&mut self) {
+        self.wrap = true;
+        self.grid = TileGrid::new(self.dimension_x * 2, self.dimension_y * 2);
+    }
+
+    /// Wraps a grid coordinate that stepped one tile past an edge.
+    pub(super) fn wrap_coordinate(&self, (x, y): (isize, isize)) -> (isize, isize) {
+        if !self.wrap {
+            return (x, y);
+        }
+        (x.rem_euclid(self.grid.width() as isize), y.rem_euclid(self.grid.height() as isize))
+    }
+}
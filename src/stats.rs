@@ -0,0 +1,52 @@
+//stats.rs
+
+use crate::genetics::{Chromosome, GeneticAlgorithm};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenerationRecord {
+    pub generation: usize,
+    pub best_fitness: f64,
+    pub average_fitness: f64,
+    pub mutation_rate: f64,
+}
+
+/// A change to the maze during a run, with the best fitness just before and just after
+/// the population was rescored against it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentChange {
+    pub generation: usize,
+    pub description: String,
+    pub best_before: f64,
+    pub best_after: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunStatistics {
+    pub generations: Vec<GenerationRecord>,
+    pub environment_changes: Vec<EnvironmentChange>,
+}
+
+impl RunStatistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_generation<C: Chromosome>(&mut self, ga: &GeneticAlgorithm<C>) {
+        self.generations.push(GenerationRecord {
+            generation: ga.generation,
+            best_fitness: ga.best_fitness,
+            average_fitness: ga.total_fitness / ga.population.len().max(1) as f64,
+            mutation_rate: ga.mutation_rate,
+        });
+    }
+
+    pub fn record_environment_change(&mut self, generation: usize, description: impl Into<String>, best_before: f64, best_after: f64) {
+        self.environment_changes.push(EnvironmentChange {
+            generation,
+            description: description.into(),
+            best_before,
+            best_after,
+        });
+    }
+}
@@ -1,97 +1,233 @@
 //visualizer.rs
 
+use crate::maze::{Lock, Maze, STAIRS_BOTH, STAIRS_DOWN, STAIRS_UP, Terrain, TileGrid, Topology};
 use macroquad::prelude::*;
 use std::time::Duration;
 use std::thread::sleep;
 
+fn tile_color(c: char) -> Color {
+    match c {
+        '#' => DARKGRAY,
+        ' ' => WHITE,
+        '*' => GREEN,
+        '~' => RED,
+        STAIRS_UP | STAIRS_DOWN | STAIRS_BOTH => GOLD,
+        _ => match Terrain::from_symbol(c) {
+            Some(Terrain::Road) => BEIGE,
+            Some(Terrain::Mud) => BROWN,
+            Some(Terrain::Water) => SKYBLUE,
+            _ => WHITE,
+        },
+    }
+}
+
 pub struct Visualizer {
     pub cell_size: f32,
-    pub maze_grid: Vec<Vec<char>>,
+    pub maze_grid: TileGrid,
+    pub waypoints: Vec<(usize, usize)>,
+    pub locks: Vec<Lock>,
+    pub hazards: Vec<(usize, usize)>,
+    /// Polygon of each grid tile in drawing units, indexed `[y][x]`. Empty for square mazes,
+    /// whose tiles are drawn as plain squares.
+    pub tile_shapes: Vec<Vec<Vec<(f32, f32)>>>,
+    pub walls: Vec<((f32, f32), (f32, f32))>,
 }
 
 impl Visualizer {
-    pub fn new(maze_grid: Vec<Vec<char>>) -> Self {
+    pub fn new(maze_grid: TileGrid) -> Self {
         let cell_size = 20.0;
-        Self { cell_size, maze_grid }
+        Self {
+            cell_size,
+            maze_grid,
+            waypoints: Vec::new(),
+            locks: Vec::new(),
+            hazards: Vec::new(),
+            tile_shapes: Vec::new(),
+            walls: Vec::new(),
+        }
     }
 
-    pub async fn _draw_maze(&self) {
-        clear_background(BLACK);
+    pub fn with_waypoints(mut self, waypoints: Vec<(usize, usize)>) -> Self {
+        self.waypoints = waypoints;
+        self
+    }
 
-        for (y, row) in self.maze_grid.iter().enumerate() {
-            for (x, &c) in row.iter().enumerate() {
-                let color = match c {
-                    '#' => DARKGRAY,
-                    ' ' => WHITE,
-                    '*' => GREEN,
-                    '~' => RED,
-                    _ => WHITE,
-                };
-
-                draw_rectangle(
-                    x as f32 * self.cell_size,
-                    y as f32 * self.cell_size,
-                    self.cell_size,
-                    self.cell_size,
-                    color,
-                );
-            }
-        }
+    pub fn with_locks(mut self, locks: Vec<Lock>) -> Self {
+        self.locks = locks;
+        self
+    }
 
-        next_frame().await;
+    pub fn with_hazards(mut self, hazards: Vec<(usize, usize)>) -> Self {
+        self.hazards = hazards;
+        self
     }
 
-    pub async fn _draw_route(&self, path: &[(usize, usize)]) {
-        for &(x, y) in path {
+    /// Takes tile shapes and cell walls from a hex or triangle maze.
+    pub fn with_geometry(mut self, maze: &Maze) -> Self {
+        if maze.topology() != Topology::Square {
+            self.tile_shapes = (0..self.maze_grid.height())
+                .map(|y| (0..self.maze_grid.width()).map(|x| maze.tile_shape((x, y))).collect())
+                .collect();
+            self.walls = maze.wall_segments();
+        }
+        self
+    }
+
+    fn fill_tile(&self, x: usize, y: usize, color: Color) {
+        let Some(shape) = self.tile_shapes.get(y).and_then(|row| row.get(x)) else {
             draw_rectangle(
                 x as f32 * self.cell_size,
                 y as f32 * self.cell_size,
                 self.cell_size,
                 self.cell_size,
-                BLUE,
+                color,
             );
+            return;
+        };
+
+        let points: Vec<Vec2> = shape.iter().map(|&(px, py)| vec2(px, py) * self.cell_size).collect();
+        for i in 1..points.len() - 1 {
+            draw_triangle(points[0], points[i], points[i + 1], color);
+        }
+    }
+
+    fn tile_center(&self, x: usize, y: usize) -> Vec2 {
+        match self.tile_shapes.get(y).and_then(|row| row.get(x)) {
+            Some(shape) => {
+                let sum = shape.iter().fold(Vec2::ZERO, |acc, &(px, py)| acc + vec2(px, py));
+                sum / shape.len() as f32 * self.cell_size
+            }
+            None => vec2(x as f32 + 0.5, y as f32 + 0.5) * self.cell_size,
+        }
+    }
+
+    fn draw_tiles(&self) {
+        for y in 0..self.maze_grid.height() {
+            for (x, c) in self.maze_grid.row(y).enumerate() {
+                self.fill_tile(x, y, tile_color(c));
+            }
+        }
+    }
+
+    fn draw_walls(&self) {
+        for &(a, b) in &self.walls {
+            let (a, b) = (vec2(a.0, a.1) * self.cell_size, vec2(b.0, b.1) * self.cell_size);
+            draw_line(a.x, a.y, b.x, b.y, self.cell_size * 0.15, DARKGRAY);
+        }
+    }
+
+    pub async fn _draw_maze(&self) {
+        clear_background(BLACK);
+
+        self.draw_tiles();
+        self.draw_walls();
+
+        next_frame().await;
+    }
+
+    pub async fn _draw_route(&self, path: &[(usize, usize)]) {
+        for &(x, y) in path {
+            self.fill_tile(x, y, BLUE);
             sleep(Duration::from_millis(100));
             next_frame().await;
         }
     }
 
-    pub async fn animate(&self, path: &[(usize, usize)]) {
+    /// Animates `path` one step per frame. `pickups` and `key_pickups` give the path index at
+    /// which each waypoint was collected and each key picked up, as returned by
+    /// `Maze::waypoint_pickups` and `Maze::key_pickups`, and `adversaries` where the
+    /// adversaries are at each step, as returned by `Maze::adversary_positions`.
+    pub async fn animate(
+        &self,
+        path: &[(usize, usize)],
+        pickups: &[Option<usize>],
+        key_pickups: &[Option<usize>],
+        adversaries: &[Vec<(usize, usize)>],
+    ) {
         for step in 0..path.len() {
             clear_background(BLACK);
 
-            for (y, row) in self.maze_grid.iter().enumerate() {
-                for (x, &c) in row.iter().enumerate() {
-                    let color = match c {
-                        '#' => DARKGRAY,
-                        ' ' => WHITE,
-                        '*' => GREEN,
-                        '~' => RED,
-                        _ => WHITE,
-                    };
-
-                    draw_rectangle(
-                        x as f32 * self.cell_size,
-                        y as f32 * self.cell_size,
-                        self.cell_size,
-                        self.cell_size,
-                        color,
-                    );
-                }
-            }
+            self.draw_tiles();
+            self.draw_hazards();
 
             for &(x, y) in &path[..=step] {
-                draw_rectangle(
-                    x as f32 * self.cell_size,
-                    y as f32 * self.cell_size,
-                    self.cell_size,
-                    self.cell_size,
-                    BLUE,
-                );
+                self.fill_tile(x, y, BLUE);
             }
+            self.draw_walls();
+
+            self.draw_waypoints(step, pickups);
+            self.draw_locks(step, key_pickups);
+            self.draw_adversaries(adversaries.get(step).map_or(&[], Vec::as_slice));
 
             next_frame().await;
             sleep(Duration::from_millis(50));
         }
     }
 
+    fn draw_waypoints(&self, step: usize, pickups: &[Option<usize>]) {
+        for (i, &(x, y)) in self.waypoints.iter().enumerate() {
+            let collected = pickups.get(i).copied().flatten().is_some_and(|at| at <= step);
+            let color = if collected { PURPLE } else { ORANGE };
+
+            self.fill_tile(x, y, color);
+            let center = self.tile_center(x, y);
+            draw_text(
+                (i + 1).to_string(),
+                center.x - self.cell_size * 0.25,
+                center.y + self.cell_size * 0.3,
+                self.cell_size,
+                BLACK,
+            );
+        }
+    }
+
+    fn draw_adversaries(&self, positions: &[(usize, usize)]) {
+        for &(x, y) in positions {
+            let center = self.tile_center(x, y);
+            draw_circle(center.x, center.y, self.cell_size * 0.4, DARKPURPLE);
+            draw_circle_lines(center.x, center.y, self.cell_size * 0.4, 2.0, BLACK);
+        }
+    }
+
+    /// Marks each hazard with a cross over a magenta tile.
+    fn draw_hazards(&self) {
+        let arm = self.cell_size * 0.3;
+        for &(x, y) in &self.hazards {
+            self.fill_tile(x, y, MAGENTA);
+            let center = self.tile_center(x, y);
+            draw_line(center.x - arm, center.y - arm, center.x + arm, center.y + arm, 2.0, BLACK);
+            draw_line(center.x - arm, center.y + arm, center.x + arm, center.y - arm, 2.0, BLACK);
+        }
+    }
+
+    /// Draws each door until its key is picked up and each key until then, labelled with a
+    /// shared letter. A ring marks a key on the step it is picked up.
+    fn draw_locks(&self, step: usize, key_pickups: &[Option<usize>]) {
+        for (i, lock) in self.locks.iter().enumerate() {
+            let picked_up = key_pickups.get(i).copied().flatten();
+            let label = char::from(b'A' + (i % 26) as u8).to_string();
+            let door = self.tile_center(lock.door.0, lock.door.1);
+            let key = self.tile_center(lock.key.0, lock.key.1);
+
+            if picked_up.is_some_and(|at| at <= step) {
+                draw_rectangle_lines(
+                    door.x - self.cell_size * 0.5,
+                    door.y - self.cell_size * 0.5,
+                    self.cell_size,
+                    self.cell_size,
+                    2.0,
+                    MAROON,
+                );
+            } else {
+                self.fill_tile(lock.door.0, lock.door.1, MAROON);
+                draw_text(&label, door.x - self.cell_size * 0.25, door.y + self.cell_size * 0.3, self.cell_size, WHITE);
+                draw_circle(key.x, key.y, self.cell_size * 0.4, YELLOW);
+                draw_text(&label, key.x - self.cell_size * 0.25, key.y + self.cell_size * 0.3, self.cell_size, BLACK);
+            }
+
+            if picked_up == Some(step) {
+                draw_circle_lines(key.x, key.y, self.cell_size * 0.9, 3.0, YELLOW);
+            }
+        }
+    }
 }